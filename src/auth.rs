@@ -0,0 +1,161 @@
+use std::fs;
+use std::sync::Arc;
+
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet, KeyAlgorithm};
+use salvo::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::config::AuthConfig;
+use crate::errors::BackendError;
+
+/// Claims of a verified token, injected in the depot for handlers
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Claims {
+    pub sub: Option<String>,
+    pub iss: Option<String>,
+    /// A single audience or a list of them
+    pub aud: Option<Value>,
+    pub jti: Option<String>,
+    pub exp: Option<u64>,
+    pub nbf: Option<u64>,
+    pub iat: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>
+}
+
+struct PublicKey {
+    key_id: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey
+}
+
+/// Verify tokens locally before any call to middleware: HS256 signed with the
+/// shared secret of middleware (`SERVER_PRIVATE_KEY`) and RS256 / ES256 signed
+/// with a key of a local JWKS file (`JWKS_FILE`)
+pub struct TokenValidator {
+    secret: Option<DecodingKey>,
+    public_keys: Vec<PublicKey>,
+    audience: Vec<String>
+}
+
+impl TokenValidator {
+    pub fn new(secret: Option<&[u8]>, jwks: Option<JwkSet>, audience: Vec<String>) -> Result<Self, String> {
+        let mut public_keys = Vec::new();
+        for jwk in jwks.map(|jwks| jwks.keys).unwrap_or_default() {
+            let algorithm = match (jwk.common.key_algorithm, &jwk.algorithm) {
+                (Some(KeyAlgorithm::RS256), AlgorithmParameters::RSA(_)) | (None, AlgorithmParameters::RSA(_)) => Algorithm::RS256,
+                (Some(KeyAlgorithm::ES256), AlgorithmParameters::EllipticCurve(_)) | (None, AlgorithmParameters::EllipticCurve(_)) => Algorithm::ES256,
+                _ => {
+                    log::warn!("JWKS key {:?} ignored, only RS256 and ES256 keys are supported", jwk.common.key_id);
+                    continue;
+                }
+            };
+            let key = DecodingKey::from_jwk(&jwk).map_err(|error| format!("Invalid JWKS key {:?}: {}", jwk.common.key_id, error))?;
+            public_keys.push(PublicKey {
+                key_id: jwk.common.key_id,
+                algorithm,
+                key
+            });
+        }
+        Ok(TokenValidator {
+            secret: secret.map(DecodingKey::from_secret),
+            public_keys,
+            audience
+        })
+    }
+
+    /// Validation is enabled when `auth.secret` (`SERVER_PRIVATE_KEY`) or
+    /// `auth.jwks_file` (`JWKS_FILE`) is set
+    pub fn from_config(config: &AuthConfig) -> Result<Option<Self>, String> {
+        let secret = config.secret.as_ref().filter(|secret| !secret.is_empty());
+        let jwks_file = config.jwks_file.as_ref().filter(|file| !file.is_empty());
+        if secret.is_none() && jwks_file.is_none() {
+            return Ok(None);
+        }
+        let jwks = match jwks_file {
+            Some(file) => {
+                let content = fs::read_to_string(file).map_err(|error| format!("JWKS file {} can not be read: {}", file, error))?;
+                Some(serde_json::from_str::<JwkSet>(&content).map_err(|error| format!("Invalid JWKS file {}: {}", file, error))?)
+            },
+            None => None
+        };
+        TokenValidator::new(secret.map(|secret| secret.as_bytes()), jwks, config.audience.clone()).map(Some)
+    }
+
+    fn decoding_key(&self, algorithm: Algorithm, key_id: Option<&str>) -> Option<&DecodingKey> {
+        if algorithm == Algorithm::HS256 {
+            return self.secret.as_ref();
+        }
+        self.public_keys.iter()
+            .filter(|public_key| public_key.algorithm == algorithm)
+            .find(|public_key| key_id.is_none() || public_key.key_id.as_deref() == key_id)
+            .map(|public_key| &public_key.key)
+    }
+
+    /// Check signature, `exp`, `nbf` and `aud` (when an audience is configured) of the
+    /// `authorization` header value, with or without the `Bearer` prefix
+    pub fn validate(&self, token_value: &str) -> Result<Claims, BackendError> {
+        let token = token_value.strip_prefix("Bearer ").or_else(|| token_value.strip_prefix("bearer ")).unwrap_or(token_value).trim();
+        let header = decode_header(token).map_err(|error| invalid_token(error.kind()))?;
+        if !matches!(header.alg, Algorithm::HS256 | Algorithm::RS256 | Algorithm::ES256) {
+            return Err(BackendError::unauthenticated("Invalid Token").with_details(json!({"reason": format!("algorithm {:?} is not allowed", header.alg)})));
+        }
+        let key = match self.decoding_key(header.alg, header.kid.as_deref()) {
+            Some(key) => key,
+            None => return Err(BackendError::unauthenticated("Invalid Token").with_details(json!({"reason": "no key to verify the signature"})))
+        };
+        let mut validation = Validation::new(header.alg);
+        validation.required_spec_claims.clear();
+        validation.validate_nbf = true;
+        validation.validate_aud = !self.audience.is_empty();
+        if !self.audience.is_empty() {
+            validation.set_audience(&self.audience);
+        }
+        decode::<Claims>(token, key, &validation)
+            .map(|token_data| token_data.claims)
+            .map_err(|error| invalid_token(error.kind()))
+    }
+}
+
+fn invalid_token(kind: &ErrorKind) -> BackendError {
+    let reason = match kind {
+        ErrorKind::ExpiredSignature => "token is expired",
+        ErrorKind::ImmatureSignature => "token is not valid yet",
+        ErrorKind::InvalidAudience => "invalid audience",
+        ErrorKind::InvalidSignature => "invalid signature",
+        _ => "malformed token"
+    };
+    BackendError::unauthenticated("Invalid Token").with_details(json!({"reason": reason}))
+}
+
+/// Reject requests without a valid token, with 401, before they reach a handler
+pub struct TokenValidation {
+    pub validator: Arc<TokenValidator>
+}
+
+#[async_trait]
+impl Handler for TokenValidation {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let result = match _req.header::<String>("authorization") {
+            Some(token_value) => self.validator.validate(&token_value),
+            None => Err(BackendError::unauthenticated("Token is Mandatory"))
+        };
+        match result {
+            Ok(claims) => {
+                _depot.inject(claims);
+            },
+            Err(error) => {
+                error.write(_req, _depot, _res).await;
+                _ctrl.skip_rest();
+            }
+        }
+    }
+}
+
+/// Claims of the token when validation is enabled
+pub fn verified_claims(_depot: &Depot) -> Option<&Claims> {
+    _depot.obtain::<Claims>()
+}
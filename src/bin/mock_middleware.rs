@@ -0,0 +1,51 @@
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use adempiere_backend_rs::mock::{MockMiddleware, MockScript};
+use clap::Parser;
+use dotenv::dotenv;
+use simple_logger::SimpleLogger;
+
+/// In-memory adempiere-middleware for develop and test without database
+#[derive(Parser, Debug)]
+#[command(name = "mock-middleware", version)]
+struct Arguments {
+    /// Address of the gRPC server
+    #[arg(long, env = "MOCK_MIDDLEWARE_ADDRESS", default_value = "0.0.0.0:50059")]
+    address: SocketAddr,
+    /// JSON file with mandatory columns by table and responses by process code
+    #[arg(long, env = "MOCK_MIDDLEWARE_SCRIPT")]
+    script: Option<PathBuf>
+}
+
+fn load_script(file: &PathBuf) -> Result<MockScript, String> {
+    let content = fs::read_to_string(file).map_err(|error| format!("Script {} can not be read: {}", file.display(), error))?;
+    serde_json::from_str(&content).map_err(|error| format!("Invalid Script {}: {}", file.display(), error))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    dotenv().ok();
+    SimpleLogger::new().env().init().unwrap();
+    let arguments = Arguments::parse();
+    let script = match &arguments.script {
+        Some(file) => match load_script(file) {
+            Ok(script) => script,
+            Err(error) => {
+                log::error!("{}", error);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => MockScript::default()
+    };
+    log::info!("Mock Middleware Address: {}", arguments.address);
+    match MockMiddleware::from_script(script).serve(arguments.address).await {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => {
+            log::error!("Mock Middleware failed: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
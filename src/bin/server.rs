@@ -1,263 +1,104 @@
-use std::env;
-use adempiere_backend_rs::middleware::{KeyValue, CreateEntityRequest, UpdateEntityRequest, DeleteEntityRequest, RunBusinessProcessRequest};
-use adempiere_backend_rs::middleware::middleware_service_client::MiddlewareServiceClient;
-use adempiere_backend_rs::models::documents::{EntityNewDocument, EntityResponse, EntityUpdateDocument, EntityDeleteDocument, RunProcessDocument, ProcessResponse};
+use std::process::ExitCode;
+use std::time::Duration;
+use adempiere_backend_rs::config::Config;
+use adempiere_backend_rs::server::build_router;
+use adempiere_backend_rs::server::shutdown::{shutdown_signal, InFlightRequests};
+use adempiere_backend_rs::tls::serve_tls;
 use dotenv::dotenv;
-use local_ip_address::local_ip;
 use salvo::prelude::*;
-extern crate serde_json;
 use simple_logger::SimpleLogger;
-use tonic::Request;
-use tonic::transport::Channel;
-use tonic::metadata::MetadataValue;
+use tokio::sync::oneshot;
+
+/// Time given to write the responses of the drained requests before exit
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     dotenv().ok();
-    SimpleLogger::new().env().init().unwrap();
-    let host =  match env::var("HOST") {
-        Ok(value) => value,
-        Err(_) => {
-            log::info!("Variable `HOST` Not found from enviroment, loaded from local IP");
-            match local_ip() {
-                Ok(value) => {
-                    let mut address = value.to_string();
-                    address.push_str(":7878");
-                    address
-                },
-                Err(_) => "127.0.0.1:7878".to_owned()
-            }
-        }.to_owned(),
-    };
-    let middleware_host = env::var("MIDDLEWARE_HOST");
-    if middleware_host.is_err() {
-        log::info!("Middleware Host not found");
-        return;  
-    }
-    log::info!("Server Address: {:?}", host.clone());
-    let router = Router::new()
-        .push(
-            Router::with_path("v1/entities")
-                .post(create_entity)
-                .patch(update_entity)
-                .delete(delete_entity)
-        )
-        .push(
-            Router::with_path("v1/process")
-                .post(run_process)
-        )
-        ;
-    log::info!("{:#?}", router);
-    let acceptor = TcpListener::new(&host).bind().await;
-    Server::new(acceptor).serve(router).await;
-}
-
-#[handler]
-async fn create_entity<'a>(_req: &mut salvo::Request, _document: EntityNewDocument, _res: &mut Response) {
-    let _entity = _document.entity;
-    if _entity.is_none() {
-        log::warn!("{:?}", "Entity Is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-       return _res.render("Entity Is Mandatory");
-    }
-    let _entity = _entity.unwrap();
-    if _entity.table_name.is_none() {
-        log::warn!("{:?}", "Table Is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Table Is Mandatory");
-    }
-    if _entity.attributes.is_none() {
-        log::warn!("{:?}", "Attributes are Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Attributes are Mandatory");
-    }
-    let token_value = _req.header::<String>("authorization");
-    if token_value.is_none() {
-        log::warn!("{:?}", "Token is Mandatory");
-        _res.status_code(StatusCode::FORBIDDEN);
-        return _res.render("Token is Mandatory");
-    }
-    let token_value = token_value.unwrap();
-    let attributes: Vec<KeyValue> = _entity.attributes.unwrap().iter().map(|value| value.to_owned().to_grpc_value()).collect();
-    let middleware_host = env::var("MIDDLEWARE_HOST").unwrap().clone();();
-    let channel = Channel::from_shared(middleware_host).unwrap().connect().await.unwrap();
-    let token: MetadataValue<_> =  token_value.parse().unwrap();
-    let mut client = MiddlewareServiceClient::with_interceptor(channel, move |mut req: Request<()>| {
-        req.metadata_mut().insert("authorization", token.clone());
-        Ok(req)
-    });
-    match client.create_entity(tonic::Request::new(CreateEntityRequest {
-        table_name: _entity.table_name.unwrap(),
-        attributes: attributes
-    })).await {
-        Ok(response) => {
-            let entity = response.get_ref();
-            log::info!("{}", entity.to_owned().id);
-            _res.status_code(StatusCode::OK);
-            _res.render(Json(EntityResponse::from_entity(entity.to_owned())));
-        }, 
+    let config = match Config::load() {
+        Ok(config) => config,
         Err(error) => {
-            log::warn!("{}", error);
-            _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-            _res.render(Json(format!("{}", error.message())));
+            eprintln!("Invalid configuration:\n{}", error);
+            return ExitCode::FAILURE;
         }
+    };
+    config.apply_timezone();
+    SimpleLogger::new().with_level(config.log_level()).init().unwrap();
+    for warning in &config.warnings {
+        log::warn!("{}", warning);
     }
-}
-
-#[handler]
-async fn update_entity<'a>(_req: &mut salvo::Request, _document: EntityUpdateDocument, _res: &mut Response) {
-    let _entity = _document.entity;
-    if _entity.is_none() {
-        log::warn!("{:?}", "Entity Is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-       return _res.render("Entity Is Mandatory");
-    }
-    let _entity = _entity.unwrap();
-    if _entity.table_name.is_none() {
-        log::warn!("{:?}", "Table Is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Table Is Mandatory");
-    }
-    if _entity.attributes.is_none() {
-        log::warn!("{:?}", "Attributes are Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Attributes are Mandatory");
-    }
-    if _entity.id.is_none() {
-        log::warn!("{:?}", "ID is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("ID is Mandatory");
-    }
-    let token_value = _req.header::<String>("authorization");
-    if token_value.is_none() {
-        log::warn!("{:?}", "Token is Mandatory");
-        _res.status_code(StatusCode::FORBIDDEN);
-        return _res.render("Token is Mandatory");
-    }
-    let token_value = token_value.unwrap();
-    let attributes: Vec<KeyValue> = _entity.attributes.unwrap().iter().map(|value| value.to_owned().to_grpc_value()).collect();
-    let middleware_host = env::var("MIDDLEWARE_HOST").unwrap().clone();();
-    let channel = Channel::from_shared(middleware_host).unwrap().connect().await.unwrap();
-    let token: MetadataValue<_> =  token_value.parse().unwrap();
-    let mut client = MiddlewareServiceClient::with_interceptor(channel, move |mut req: Request<()>| {
-        req.metadata_mut().insert("authorization", token.clone());
-        Ok(req)
-    });
-    match client.update_entity(tonic::Request::new(UpdateEntityRequest {
-        table_name: _entity.table_name.unwrap(),
-        id: _entity.id.unwrap(),
-        attributes: attributes
-    })).await {
-        Ok(response) => {
-            let entity = response.get_ref();
-            log::info!("{}", entity.to_owned().id);
-            _res.status_code(StatusCode::OK);
-            _res.render(Json(EntityResponse::from_entity(entity.to_owned())));
-        }, 
+    let router_config = match config.router_config() {
+        Ok(router_config) => router_config,
         Err(error) => {
-            log::warn!("{}", error);
-            _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-            _res.render(Json(format!("{}", error.message())));
+            log::error!("Invalid configuration: {}", error);
+            return ExitCode::FAILURE;
         }
+    };
+    let host = config.listen_address();
+    log::info!("Server Address: {:?}", host.clone());
+    log::info!("Middleware Host: {:?}", config.middleware.host.clone().unwrap_or_default());
+    match router_config.token_validator {
+        Some(_) => log::info!("Token Validation Enabled"),
+        None => log::info!("Token Validation Disabled, tokens are verified by middleware")
     }
-}
-
-#[handler]
-async fn delete_entity<'a>(_req: &mut salvo::Request, _document: EntityDeleteDocument, _res: &mut Response) {
-    let _entity = _document.entity;
-    if _entity.is_none() {
-        log::warn!("{:?}", "Entity Is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Entity Is Mandatory");
-    }
-    let _entity = _entity.unwrap();
-    if _entity.table_name.is_none() {
-        log::warn!("{:?}", "Table Is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Table Is Mandatory");
-    }
-    if _entity.id.is_none() {
-        log::warn!("{:?}", "ID is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("ID is Mandatory");
-    }
-    let token_value = _req.header::<String>("authorization");
-    if token_value.is_none() {
-        log::warn!("{:?}", "Token is Mandatory");
-        _res.status_code(StatusCode::FORBIDDEN);
-        return _res.render("Token is Mandatory");
-    }
-    let token_value = token_value.unwrap();
-    let middleware_host = env::var("MIDDLEWARE_HOST").unwrap().clone();();
-    let channel = Channel::from_shared(middleware_host).unwrap().connect().await.unwrap();
-    let token: MetadataValue<_> =  token_value.parse().unwrap();
-    let mut client = MiddlewareServiceClient::with_interceptor(channel, move |mut req: Request<()>| {
-        req.metadata_mut().insert("authorization", token.clone());
-        Ok(req)
-    });
-    match client.delete_entity(tonic::Request::new(DeleteEntityRequest {
-        table_name: _entity.table_name.unwrap(),
-        id: _entity.id.unwrap()
-    })).await {
-        Ok(_) => {
-            log::info!("{}", _entity.id.unwrap());
-            _res.status_code(StatusCode::OK);
-            _res.render(Json("Ok"));
-        }, 
-        Err(error) => {
-            log::warn!("{}", error);
-            _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-            _res.render(Json(format!("{}", error.message())));
+    salvo::http::request::set_secure_max_size(config.limits.max_body_size);
+    let in_flight = InFlightRequests::new();
+    let router = Router::new()
+        .hoop(in_flight.clone())
+        .push(build_router(router_config));
+    log::info!("{:#?}", router);
+    let (stop, stopped) = oneshot::channel::<()>();
+    let stopped = async {
+        stopped.await.ok();
+    };
+    //  The drain timeout is applied below, the requests aborted by it are logged
+    let mut server = if config.server.tls.is_enabled() {
+        let listener = match tokio::net::TcpListener::bind(&host).await {
+            Ok(listener) => listener,
+            Err(error) => {
+                log::error!("Server Address {} can not be used: {}", host, error);
+                return ExitCode::FAILURE;
+            }
+        };
+        log::info!("HTTPS Enabled, certificate: {}", config.server.tls.cert_file.clone().unwrap_or_default());
+        let tls = config.server.tls.clone();
+        tokio::spawn(async move {
+            if let Err(error) = serve_tls(listener, Service::new(router), &tls, stopped).await {
+                log::error!("HTTPS Server failed: {}", error);
+            }
+        })
+    } else {
+        let acceptor = match TcpListener::new(&host).try_bind().await {
+            Ok(acceptor) => acceptor,
+            Err(error) => {
+                log::error!("Server Address {} can not be used: {}", host, error);
+                return ExitCode::FAILURE;
+            }
+        };
+        tokio::spawn(Server::new(acceptor).serve_with_graceful_shutdown(router, stopped, None))
+    };
+    let drain_timeout = config.drain_timeout();
+    tokio::select! {
+        _ = shutdown_signal() => {},
+        _ = &mut server => {
+            log::error!("Server stopped unexpectedly");
+            return ExitCode::FAILURE;
         }
     }
+    //  New connections are not accepted from here, requests in flight can finish
+    log::info!("Shutting down, waiting up to {:?} for {} requests in flight", drain_timeout, in_flight.count());
+    in_flight.close();
+    let _ = stop.send(());
+    let aborted = in_flight.drain(drain_timeout).await;
+    for request in &aborted {
+        log::warn!("Request aborted by shutdown: {} {} (running for {:?})", request.method, request.path, request.started.elapsed());
+    }
+    if aborted.is_empty() {
+        log::info!("All requests in flight were completed");
+        let _ = tokio::time::timeout(FLUSH_TIMEOUT, server).await;
+    } else {
+        server.abort();
+    }
+    log::info!("Server stopped");
+    ExitCode::SUCCESS
 }
-#[handler]
-async fn run_process<'a>(_req: &mut salvo::Request, _document: RunProcessDocument, _res: &mut Response) {
-    let _process = _document.process;
-    if _process.is_none() {
-        log::warn!("{:?}", "Process Is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Process Is Mandatory");
-    }
-    let _process = _process.unwrap();
-    if _process.process_code.is_none() {
-        log::warn!("{:?}", "Process Code is Mandatory");
-        _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-        return _res.render("Process Code is Mandatory");
-    }
-    let token_value = _req.header::<String>("authorization");
-    if token_value.is_none() {
-        log::warn!("{:?}", "Token is Mandatory");
-        _res.status_code(StatusCode::FORBIDDEN);
-        return _res.render("Token is Mandatory");
-    }
-    let token_value = token_value.unwrap();
-    let parameters: Vec<KeyValue> = _process.parameters.unwrap_or_default().iter().map(|value| value.to_owned().to_grpc_value()).collect();
-    let middleware_host = env::var("MIDDLEWARE_HOST").unwrap().clone();();
-    let channel = Channel::from_shared(middleware_host).unwrap().connect().await.unwrap();
-    let token: MetadataValue<_> =  token_value.parse().unwrap();
-    let mut client = MiddlewareServiceClient::with_interceptor(channel, move |mut req: Request<()>| {
-        req.metadata_mut().insert("authorization", token.clone());
-        Ok(req)
-    });
-    match client.run_business_process(tonic::Request::new(RunBusinessProcessRequest {
-        process_code: _process.process_code.unwrap(),
-        table_name: _process.table_name.unwrap_or_default(),
-        id: _process.id.unwrap_or_default(),
-        table_selected_id: _process.table_selected_id.unwrap_or_default(),
-        parameters: parameters,
-        ..Default::default()
-    })).await {
-        Ok(response) => {
-            let process_respose = response.get_ref();
-            log::info!("{:?}", process_respose.to_owned());
-            _res.status_code(StatusCode::OK);
-            _res.render(Json(ProcessResponse::from_process_response(process_respose.to_owned())));
-        }, 
-        Err(error) => {
-            log::warn!("{}", error);
-            _res.status_code(StatusCode::INTERNAL_SERVER_ERROR);
-            _res.render(Json(format!("{}", error.message())));
-        }
-    }
-}
\ No newline at end of file
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+use dotenv::dotenv;
+use hmac::{Hmac, Mac};
+use jwt::{AlgorithmType, Header, SignWithKey, Token};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use uuid::Uuid;
+
+// Create alias for HMAC-SHA256
+type HmacSha256 = Hmac<Sha256>;
+
+/// Build and sign (HS256) a token for call adempiere-middleware from a service account.
+/// The secret is the first one found of `--secret`, `--secret-file`, `SERVER_PRIVATE_KEY`
+/// and `SERVER_PRIVATE_KEY_FILE`, so an argument always wins over the environment
+#[derive(Parser, Debug)]
+#[command(name = "token", version)]
+struct Arguments {
+    /// Secret shared with middleware, `SERVER_PRIVATE_KEY` when it is not set
+    #[arg(long, conflicts_with = "secret_file")]
+    secret: Option<String>,
+    /// File with the secret shared with middleware, `SERVER_PRIVATE_KEY_FILE` when it is not set
+    #[arg(long)]
+    secret_file: Option<PathBuf>,
+    /// Subject (`sub`)
+    #[arg(long)]
+    subject: String,
+    /// Audience (`aud`)
+    #[arg(long)]
+    audience: Option<String>,
+    /// Issuer (`iss`)
+    #[arg(long)]
+    issuer: Option<String>,
+    /// Token ID (`jti`), a random UUID by default
+    #[arg(long)]
+    id: Option<String>,
+    /// Seconds until the token expires (`exp`), 0 for a token without expiration
+    #[arg(long, default_value_t = 3600)]
+    expires_in: u64,
+    /// Print the token with the `Bearer ` prefix, as sent in the `Authorization` header
+    #[arg(long)]
+    bearer: bool
+}
+
+fn secret(arguments: &Arguments) -> Result<String, String> {
+    if let Some(secret) = &arguments.secret {
+        return Ok(secret.to_owned());
+    }
+    let secret_file = match &arguments.secret_file {
+        Some(file) => Some(file.to_owned()),
+        None => {
+            if let Ok(secret) = env::var("SERVER_PRIVATE_KEY") {
+                return Ok(secret);
+            }
+            env::var_os("SERVER_PRIVATE_KEY_FILE").map(PathBuf::from)
+        }
+    };
+    match secret_file {
+        Some(file) => fs::read_to_string(&file)
+            .map(|secret| secret.trim().to_owned())
+            .map_err(|error| format!("Secret file {} can not be read: {}", file.display(), error)),
+        None => Err("Secret is Mandatory, use --secret, --secret-file, SERVER_PRIVATE_KEY or SERVER_PRIVATE_KEY_FILE".to_owned())
+    }
+}
+
+fn sign(arguments: &Arguments) -> Result<String, String> {
+    let secret = secret(arguments)?;
+    if secret.is_empty() {
+        return Err("Secret is Mandatory".to_owned());
+    }
+    let key = HmacSha256::new_from_slice(secret.as_bytes()).map_err(|error| error.to_string())?;
+    let issued_at = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|error| error.to_string())?.as_secs();
+    let mut claims: BTreeMap<&str, Value> = BTreeMap::new();
+    claims.insert("sub", json!(arguments.subject));
+    claims.insert("jti", json!(arguments.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string())));
+    claims.insert("iat", json!(issued_at));
+    if let Some(audience) = &arguments.audience {
+        claims.insert("aud", json!(audience));
+    }
+    if let Some(issuer) = &arguments.issuer {
+        claims.insert("iss", json!(issuer));
+    }
+    if arguments.expires_in > 0 {
+        claims.insert("exp", json!(issued_at + arguments.expires_in));
+    }
+    let header = Header {
+        algorithm: AlgorithmType::Hs256,
+        ..Default::default()
+    };
+    let token = Token::new(header, claims).sign_with_key(&key).map_err(|error| error.to_string())?;
+    Ok(token.as_str().to_owned())
+}
+
+fn main() -> ExitCode {
+    dotenv().ok();
+    let arguments = Arguments::parse();
+    match sign(&arguments) {
+        Ok(token) => {
+            if arguments.bearer {
+                println!("Bearer {}", token);
+            } else {
+                println!("{}", token);
+            }
+            ExitCode::SUCCESS
+        },
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
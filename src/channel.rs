@@ -0,0 +1,116 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use hyper::client::HttpConnector;
+use hyper::service::Service;
+use hyper::Uri;
+use tokio::net::TcpStream;
+use tonic::{Request, Status};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Channel, Endpoint, Error};
+
+use crate::metrics::Metrics;
+use crate::middleware::middleware_service_client::MiddlewareServiceClient;
+
+pub type MiddlewareClient = MiddlewareServiceClient<InterceptedService<Channel, TokenInterceptor>>;
+
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+/// Connect timeout of `middleware_endpoint`
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Endpoint of adempiere-middleware with the default timeouts and keep alive
+pub fn middleware_endpoint(middleware_host: String) -> Result<Endpoint, Error> {
+    Ok(Endpoint::from_shared(middleware_host)?
+        .connect_timeout(DEFAULT_CONNECT_TIMEOUT)
+        .tcp_keepalive(Some(TCP_KEEPALIVE))
+        .http2_keep_alive_interval(Duration::from_secs(30))
+        .keep_alive_while_idle(true))
+}
+
+/// Build the shared channel to adempiere-middleware. The connection is
+/// established on first use and re-established by tonic if it drops, so a
+/// single channel can be cloned into every request.
+pub fn connect_lazy(middleware_host: String) -> Result<Channel, Error> {
+    Ok(middleware_endpoint(middleware_host)?.connect_lazy())
+}
+
+/// TCP connector of the channel that counts its connections in the metrics, a
+/// connection after the first one means that the channel was re-established.
+/// `Endpoint::connect_with_connector_lazy` does not apply the connect timeout of
+/// the endpoint, so the connector has its own
+#[derive(Clone)]
+pub struct CountingConnector {
+    http: HttpConnector,
+    metrics: Metrics
+}
+
+impl CountingConnector {
+    /// TCP options of `middleware_endpoint` and the timeout of a connection
+    pub fn new(connect_timeout: Duration, metrics: Metrics) -> Self {
+        let mut http = HttpConnector::new();
+        http.set_connect_timeout(Some(connect_timeout));
+        http.enforce_http(false);
+        http.set_nodelay(true);
+        http.set_keepalive(Some(TCP_KEEPALIVE));
+        CountingConnector {
+            http,
+            metrics
+        }
+    }
+}
+
+impl Service<Uri> for CountingConnector {
+    type Response = TcpStream;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = Pin<Box<dyn Future<Output = Result<TcpStream, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, context: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.http.poll_ready(context).map_err(Into::into)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connecting = self.http.call(uri);
+        let metrics = self.metrics.clone();
+        Box::pin(async move {
+            let result = connecting.await;
+            metrics.observe_connection(result.is_ok());
+            // A timeout or an unreachable network is `UNAVAILABLE` as a refused connection
+            result.map_err(|error| Box::new(Status::unavailable(format!("Middleware can not be reached: {}", error))) as Self::Error)
+        })
+    }
+}
+
+/// Build the shared channel to adempiere-middleware as `connect_lazy`, with its connections in the metrics.
+/// `connect_timeout` must be the one of the endpoint
+pub fn connect_lazy_with_metrics(endpoint: &Endpoint, connect_timeout: Duration, metrics: Metrics) -> Channel {
+    endpoint.connect_with_connector_lazy(CountingConnector::new(connect_timeout, metrics))
+}
+
+/// Attach the `authorization` token of the incoming REST request to every gRPC call
+#[derive(Clone)]
+pub struct TokenInterceptor {
+    token: MetadataValue<Ascii>
+}
+
+impl TokenInterceptor {
+    pub fn new(token: MetadataValue<Ascii>) -> Self {
+        TokenInterceptor {
+            token
+        }
+    }
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.metadata_mut().insert("authorization", self.token.clone());
+        Ok(request)
+    }
+}
+
+pub fn middleware_client(channel: Channel, token: MetadataValue<Ascii>) -> MiddlewareClient {
+    MiddlewareServiceClient::with_interceptor(channel, TokenInterceptor::new(token))
+}
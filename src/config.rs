@@ -0,0 +1,473 @@
+use std::env;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use local_ip_address::local_ip;
+use log::LevelFilter;
+use serde::Deserialize;
+use tonic::transport::Channel;
+
+use crate::auth::TokenValidator;
+use crate::channel::{connect_lazy_with_metrics, middleware_endpoint};
+use crate::metrics::Metrics;
+use crate::models::query::MAX_PAGE_SIZE;
+use crate::server::{Limits, RouterConfig};
+use crate::server::health::HealthCheck;
+use crate::server::openapi::{check_swagger_ui_assets, ApiDocConfig};
+use crate::tls::{client_tls_config, read_certificates, read_private_key, rustls_server_config};
+
+/// File read when `CONFIG_FILE` is not set, it is optional
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Configuration of the server, read from a TOML file and overridden by
+/// environment variables. See `config.example.toml`
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub middleware: MiddlewareConfig,
+    pub auth: AuthConfig,
+    pub limits: LimitsConfig,
+    pub log: LogConfig,
+    /// Problems that do not stop the server, logged once the logger is initialized
+    #[serde(skip)]
+    pub warnings: Vec<String>
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Listen address (`HOST`), the local IP with `port` when it is not set
+    pub address: Option<String>,
+    pub port: u16,
+    /// Seconds to wait for requests in flight on shutdown (`DRAIN_TIMEOUT`)
+    pub drain_timeout: u64,
+    /// Time zone of the dates without offset, e.g. `America/Caracas` (`TZ`), the one of the system when it is not set
+    pub timezone: Option<String>,
+    /// URL of the endpoints in `openapi.json` (`OPENAPI_SERVER_URL`), the path of the request when it is not set
+    pub openapi_server_url: Option<String>,
+    /// Directory with the files of swagger-ui-dist served in `swagger-ui/` (`SWAGGER_UI_ASSETS`), unpkg when it is not set
+    pub swagger_ui_assets: Option<String>,
+    pub tls: ServerTlsConfig
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            address: None,
+            port: DEFAULT_PORT,
+            drain_timeout: 30,
+            timezone: None,
+            openapi_server_url: None,
+            swagger_ui_assets: None,
+            tls: ServerTlsConfig::default()
+        }
+    }
+}
+
+/// HTTPS of the listener, enabled when the certificate and the key are set
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerTlsConfig {
+    /// PEM certificate chain (`TLS_CERT_FILE`)
+    pub cert_file: Option<String>,
+    /// PEM private key, PKCS#8 or RSA (`TLS_KEY_FILE`)
+    pub key_file: Option<String>,
+    /// Seconds between checks for changes of the files (`TLS_RELOAD_INTERVAL`)
+    pub reload_interval: u64
+}
+
+impl Default for ServerTlsConfig {
+    fn default() -> Self {
+        ServerTlsConfig {
+            cert_file: None,
+            key_file: None,
+            reload_interval: 10
+        }
+    }
+}
+
+impl ServerTlsConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.cert_file.is_some() || self.key_file.is_some()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct MiddlewareConfig {
+    /// URL of adempiere-middleware (`MIDDLEWARE_HOST`), mandatory
+    pub host: Option<String>,
+    /// Seconds to wait for a connection (`MIDDLEWARE_CONNECT_TIMEOUT`)
+    pub connect_timeout: u64,
+    /// Seconds to wait for each call (`MIDDLEWARE_TIMEOUT`), without limit when it is not set
+    pub timeout: Option<u64>,
+    /// Service asked with the gRPC Health protocol by `health/ready` (`MIDDLEWARE_HEALTH_SERVICE`), empty for the whole server
+    pub health_service: String,
+    /// Seconds to wait for the health check of `health/ready` (`MIDDLEWARE_HEALTH_TIMEOUT`)
+    pub health_timeout: u64,
+    pub tls: MiddlewareTlsConfig
+}
+
+impl Default for MiddlewareConfig {
+    fn default() -> Self {
+        MiddlewareConfig {
+            host: None,
+            connect_timeout: 5,
+            timeout: None,
+            health_service: String::new(),
+            health_timeout: 2,
+            tls: MiddlewareTlsConfig::default()
+        }
+    }
+}
+
+impl MiddlewareConfig {
+    /// TLS is used with an `https://` host
+    pub fn is_tls(&self) -> bool {
+        self.host.as_ref().map(|host| host.to_lowercase().starts_with("https://")).unwrap_or_default()
+    }
+}
+
+/// TLS of the channel to middleware, used with an `https://` host
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct MiddlewareTlsConfig {
+    /// PEM certificate of the CA of middleware (`MIDDLEWARE_TLS_CA_FILE`), the system roots are also trusted
+    pub ca_file: Option<String>,
+    /// PEM client certificate for mutual TLS (`MIDDLEWARE_TLS_CERT_FILE`)
+    pub cert_file: Option<String>,
+    /// PEM private key of the client certificate (`MIDDLEWARE_TLS_KEY_FILE`)
+    pub key_file: Option<String>,
+    /// Name expected in the certificate of middleware (`MIDDLEWARE_TLS_DOMAIN`), the host of the URL when it is not set
+    pub domain_name: Option<String>
+}
+
+impl MiddlewareTlsConfig {
+    pub fn is_set(&self) -> bool {
+        self.ca_file.is_some() || self.cert_file.is_some() || self.key_file.is_some() || self.domain_name.is_some()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Secret shared with middleware for HS256 tokens (`SERVER_PRIVATE_KEY`)
+    pub secret: Option<String>,
+    /// JWKS file with the keys of RS256 / ES256 tokens (`JWKS_FILE`)
+    pub jwks_file: Option<String>,
+    /// Accepted audiences (`JWT_AUDIENCE`, comma separated)
+    pub audience: Vec<String>
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Max size of a request body in bytes (`MAX_BODY_SIZE`)
+    pub max_body_size: usize,
+    /// Max records by page of a list (`MAX_PAGE_SIZE`)
+    pub max_page_size: i32
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        LimitsConfig {
+            max_body_size: 64 * 1024,
+            max_page_size: MAX_PAGE_SIZE
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// `error`, `warn`, `info`, `debug` or `trace` (`LOG_LEVEL`)
+    pub level: String
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: "info".to_owned()
+        }
+    }
+}
+
+/// A name of the time zone database (`TZDIR` or `/usr/share/zoneinfo`), a POSIX rule
+/// such as `EST5EDT` is not checked. When the database is not found the value is accepted
+fn is_known_timezone(timezone: &str) -> bool {
+    let name = timezone.strip_prefix(':').unwrap_or(timezone);
+    if name.is_empty() {
+        return false;
+    }
+    if name.chars().any(|character| character.is_ascii_digit()) && !name.contains('/') {
+        return true;
+    }
+    let directory = env::var("TZDIR").unwrap_or_else(|_| "/usr/share/zoneinfo".to_owned());
+    !Path::new(&directory).is_dir() || Path::new(&directory).join(name).is_file()
+}
+
+fn parse_variable<T: FromStr>(name: &str, value: String) -> Result<T, String> {
+    value.trim().parse::<T>().map_err(|_| format!("Invalid value of {}: {}", name, value))
+}
+
+impl Config {
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|error| error.to_string())
+    }
+
+    /// Read the file of `CONFIG_FILE` (or `config.toml` when it exists), apply the
+    /// environment variables and validate the result
+    pub fn load() -> Result<Self, String> {
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(file) => Config::from_file(&file)?,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => Config::from_file(DEFAULT_CONFIG_FILE)?,
+            Err(_) => Config::default()
+        };
+        config.apply_env(|name| env::var(name).ok())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_file(file: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(file).map_err(|error| format!("Config file {} can not be read: {}", file, error))?;
+        Config::from_toml(&content).map_err(|error| format!("Invalid config file {}: {}", file, error))
+    }
+
+    /// Environment variables take precedence over the file
+    pub fn apply_env(&mut self, variable: impl Fn(&str) -> Option<String>) -> Result<(), String> {
+        let variable = |name: &str| variable(name).filter(|value| !value.is_empty());
+        if let Some(value) = variable("HOST") {
+            self.server.address = Some(value);
+        }
+        if let Some(value) = variable("PORT") {
+            self.server.port = parse_variable("PORT", value)?;
+        }
+        if let Some(value) = variable("DRAIN_TIMEOUT") {
+            self.server.drain_timeout = parse_variable("DRAIN_TIMEOUT", value)?;
+        }
+        if let Some(value) = variable("TZ") {
+            self.server.timezone = Some(value);
+        }
+        if let Some(value) = variable("OPENAPI_SERVER_URL") {
+            self.server.openapi_server_url = Some(value);
+        }
+        if let Some(value) = variable("SWAGGER_UI_ASSETS") {
+            self.server.swagger_ui_assets = Some(value);
+        }
+        if let Some(value) = variable("TLS_CERT_FILE") {
+            self.server.tls.cert_file = Some(value);
+        }
+        if let Some(value) = variable("TLS_KEY_FILE") {
+            self.server.tls.key_file = Some(value);
+        }
+        if let Some(value) = variable("TLS_RELOAD_INTERVAL") {
+            self.server.tls.reload_interval = parse_variable("TLS_RELOAD_INTERVAL", value)?;
+        }
+        if let Some(value) = variable("MIDDLEWARE_HOST") {
+            self.middleware.host = Some(value);
+        }
+        if let Some(value) = variable("MIDDLEWARE_CONNECT_TIMEOUT") {
+            self.middleware.connect_timeout = parse_variable("MIDDLEWARE_CONNECT_TIMEOUT", value)?;
+        }
+        if let Some(value) = variable("MIDDLEWARE_TIMEOUT") {
+            self.middleware.timeout = Some(parse_variable("MIDDLEWARE_TIMEOUT", value)?);
+        }
+        if let Some(value) = variable("MIDDLEWARE_HEALTH_SERVICE") {
+            self.middleware.health_service = value;
+        }
+        if let Some(value) = variable("MIDDLEWARE_HEALTH_TIMEOUT") {
+            self.middleware.health_timeout = parse_variable("MIDDLEWARE_HEALTH_TIMEOUT", value)?;
+        }
+        if let Some(value) = variable("MIDDLEWARE_TLS_CA_FILE") {
+            self.middleware.tls.ca_file = Some(value);
+        }
+        if let Some(value) = variable("MIDDLEWARE_TLS_CERT_FILE") {
+            self.middleware.tls.cert_file = Some(value);
+        }
+        if let Some(value) = variable("MIDDLEWARE_TLS_KEY_FILE") {
+            self.middleware.tls.key_file = Some(value);
+        }
+        if let Some(value) = variable("MIDDLEWARE_TLS_DOMAIN") {
+            self.middleware.tls.domain_name = Some(value);
+        }
+        if let Some(value) = variable("SERVER_PRIVATE_KEY") {
+            self.auth.secret = Some(value);
+        }
+        if let Some(value) = variable("JWKS_FILE") {
+            self.auth.jwks_file = Some(value);
+        }
+        if let Some(value) = variable("JWT_AUDIENCE") {
+            self.auth.audience = value.split(',').map(|audience| audience.trim().to_owned()).filter(|audience| !audience.is_empty()).collect();
+        }
+        if let Some(value) = variable("MAX_BODY_SIZE") {
+            self.limits.max_body_size = parse_variable("MAX_BODY_SIZE", value)?;
+        }
+        if let Some(value) = variable("MAX_PAGE_SIZE") {
+            self.limits.max_page_size = parse_variable("MAX_PAGE_SIZE", value)?;
+        }
+        if let Some(value) = variable("LOG_LEVEL") {
+            self.log.level = value;
+        } else if let Some(value) = variable("RUST_LOG") {
+            //  Other tools read RUST_LOG as a filter, e.g. `info,hyper=warn`, it is only used when it is a level
+            match value.trim().parse::<LevelFilter>() {
+                Ok(_) => self.log.level = value.trim().to_owned(),
+                Err(_) => self.warnings.push(format!("RUST_LOG is not a log level, the level {} is used instead (set LOG_LEVEL): {}", self.log.level, value))
+            }
+        }
+        Ok(())
+    }
+
+    /// All the problems found, one by line
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if let Some(address) = &self.server.address {
+            if address.parse::<SocketAddr>().is_err() {
+                errors.push(format!("server.address (HOST) must be an IP and port, e.g. 0.0.0.0:{}: {}", DEFAULT_PORT, address));
+            }
+        }
+        if let Some(timezone) = &self.server.timezone {
+            if !is_known_timezone(timezone) {
+                errors.push(format!("server.timezone (TZ) is not a time zone of the system, e.g. America/Caracas: {}", timezone));
+            }
+        }
+        if let Some(directory) = &self.server.swagger_ui_assets {
+            if let Err(error) = check_swagger_ui_assets(Path::new(directory)) {
+                errors.push(format!("server.swagger_ui_assets (SWAGGER_UI_ASSETS): {}", error));
+            }
+        }
+        let server_tls = &self.server.tls;
+        if server_tls.is_enabled() {
+            match (&server_tls.cert_file, &server_tls.key_file) {
+                (Some(cert_file), Some(key_file)) => if let Err(error) = rustls_server_config(cert_file, key_file) {
+                    errors.push(format!("server.tls (TLS_CERT_FILE, TLS_KEY_FILE): {}", error));
+                },
+                _ => errors.push("server.tls.cert_file (TLS_CERT_FILE) and server.tls.key_file (TLS_KEY_FILE) must be set together".to_owned())
+            }
+            if server_tls.reload_interval == 0 {
+                errors.push("server.tls.reload_interval (TLS_RELOAD_INTERVAL) must be greater than 0".to_owned());
+            }
+        }
+        match &self.middleware.host {
+            None => errors.push("middleware.host (MIDDLEWARE_HOST) is mandatory, e.g. http://0.0.0.0:50059".to_owned()),
+            Some(host) => if let Err(error) = middleware_endpoint(host.to_owned()) {
+                errors.push(format!("middleware.host (MIDDLEWARE_HOST) is not a valid URL: {}: {}", host, error));
+            }
+        }
+        let middleware_tls = &self.middleware.tls;
+        if middleware_tls.is_set() && self.middleware.host.is_some() && !self.middleware.is_tls() {
+            errors.push("middleware.tls is only used with an https:// middleware.host (MIDDLEWARE_HOST)".to_owned());
+        }
+        if let Some(ca_file) = &middleware_tls.ca_file {
+            if let Err(error) = read_certificates(ca_file) {
+                errors.push(format!("middleware.tls.ca_file (MIDDLEWARE_TLS_CA_FILE): {}", error));
+            }
+        }
+        match (&middleware_tls.cert_file, &middleware_tls.key_file) {
+            (Some(cert_file), Some(key_file)) => {
+                if let Err(error) = read_certificates(cert_file) {
+                    errors.push(format!("middleware.tls.cert_file (MIDDLEWARE_TLS_CERT_FILE): {}", error));
+                }
+                if let Err(error) = read_private_key(key_file) {
+                    errors.push(format!("middleware.tls.key_file (MIDDLEWARE_TLS_KEY_FILE): {}", error));
+                }
+            },
+            (None, None) => {},
+            _ => errors.push("middleware.tls.cert_file (MIDDLEWARE_TLS_CERT_FILE) and middleware.tls.key_file (MIDDLEWARE_TLS_KEY_FILE) must be set together".to_owned())
+        }
+        if self.middleware.connect_timeout == 0 {
+            errors.push("middleware.connect_timeout (MIDDLEWARE_CONNECT_TIMEOUT) must be greater than 0".to_owned());
+        }
+        if self.middleware.timeout == Some(0) {
+            errors.push("middleware.timeout (MIDDLEWARE_TIMEOUT) must be greater than 0".to_owned());
+        }
+        if self.middleware.health_timeout == 0 {
+            errors.push("middleware.health_timeout (MIDDLEWARE_HEALTH_TIMEOUT) must be greater than 0".to_owned());
+        }
+        if let Some(file) = &self.auth.jwks_file {
+            if !Path::new(file).is_file() {
+                errors.push(format!("auth.jwks_file (JWKS_FILE) does not exist: {}", file));
+            }
+        }
+        if self.limits.max_body_size == 0 {
+            errors.push("limits.max_body_size (MAX_BODY_SIZE) must be greater than 0".to_owned());
+        }
+        if self.limits.max_page_size <= 0 {
+            errors.push("limits.max_page_size (MAX_PAGE_SIZE) must be greater than 0".to_owned());
+        }
+        if self.log.level.parse::<LevelFilter>().is_err() {
+            errors.push(format!("log.level (LOG_LEVEL) must be one of off, error, warn, info, debug or trace: {}", self.log.level));
+        }
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(errors.join("\n"))
+    }
+
+    /// `server.address`, or the local IP (loopback when it is not found) with `server.port`
+    pub fn listen_address(&self) -> String {
+        match &self.server.address {
+            Some(address) => address.to_owned(),
+            None => {
+                log::info!("Variable `HOST` Not found from enviroment, loaded from local IP");
+                match local_ip() {
+                    Ok(ip) => format!("{}:{}", ip, self.server.port),
+                    Err(_) => format!("127.0.0.1:{}", self.server.port)
+                }
+            }
+        }
+    }
+
+    /// Set `server.timezone` as the time zone of the process (`TZ`), it is used for the dates without
+    /// offset. Call it at start, before other threads read the environment
+    pub fn apply_timezone(&self) {
+        if let Some(timezone) = &self.server.timezone {
+            env::set_var("TZ", timezone);
+        }
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.server.drain_timeout)
+    }
+
+    pub fn log_level(&self) -> LevelFilter {
+        self.log.level.parse().unwrap_or(LevelFilter::Info)
+    }
+
+    /// Channel to middleware, its connections are counted in the metrics
+    pub fn middleware_channel(&self, metrics: &Metrics) -> Result<Channel, String> {
+        let host = self.middleware.host.clone().ok_or_else(|| "Middleware Host not found".to_owned())?;
+        let connect_timeout = Duration::from_secs(self.middleware.connect_timeout);
+        let mut endpoint = middleware_endpoint(host).map_err(|error| error.to_string())?
+            .connect_timeout(connect_timeout);
+        if let Some(timeout) = self.middleware.timeout {
+            endpoint = endpoint.timeout(Duration::from_secs(timeout));
+        }
+        if self.middleware.is_tls() {
+            endpoint = endpoint.tls_config(client_tls_config(&self.middleware.tls)?).map_err(|error| error.to_string())?;
+        }
+        Ok(connect_lazy_with_metrics(&endpoint, connect_timeout, metrics.clone()))
+    }
+
+    /// Everything the router needs, built from this configuration
+    pub fn router_config(&self) -> Result<RouterConfig, String> {
+        let metrics = Metrics::new();
+        let mut router_config = RouterConfig::new(self.middleware_channel(&metrics)?).with_limits(Limits {
+            max_page_size: self.limits.max_page_size
+        }).with_health_check(HealthCheck {
+            service: self.middleware.health_service.clone(),
+            timeout: Duration::from_secs(self.middleware.health_timeout)
+        }).with_metrics(metrics).with_api_doc(ApiDocConfig {
+            server_url: self.server.openapi_server_url.clone(),
+            swagger_ui_assets: self.server.swagger_ui_assets.as_ref().map(PathBuf::from)
+        });
+        if let Some(token_validator) = TokenValidator::from_config(&self.auth)? {
+            router_config = router_config.with_token_validator(token_validator);
+        }
+        Ok(router_config)
+    }
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+
+use salvo::prelude::*;
+use salvo::http::header::{HeaderValue, RETRY_AFTER};
+use serde::Serialize;
+use serde_json::Value;
+use tonic::{Code, Status};
+use tonic::transport::TimeoutExpired;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::metrics::ERROR_CODE_LABEL;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// Seconds a client should wait before retrying when middleware is unavailable
+pub const RETRY_AFTER_SECONDS: u64 = 5;
+
+/// Error returned by REST handlers, rendered as a JSON envelope
+/// `{code, message, details, request_id}` with a matching HTTP status
+#[derive(Debug, Clone)]
+pub struct BackendError {
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+    pub details: Option<Value>,
+    pub retry_after: Option<u64>
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+pub struct ErrorResponse {
+    pub code: String,
+    pub message: String,
+    #[schema(value_type = Option<Object>)]
+    pub details: Option<Value>,
+    pub request_id: String
+}
+
+impl BackendError {
+    pub fn new(status: StatusCode, code: Code, message: impl Into<String>) -> Self {
+        BackendError {
+            status,
+            code: code_name(code).to_owned(),
+            message: message.into(),
+            details: None,
+            retry_after: None
+        }
+    }
+
+    /// Local validation error, e.g. a mandatory field missing from the payload
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        BackendError::new(StatusCode::BAD_REQUEST, Code::InvalidArgument, message)
+    }
+
+    pub fn unauthenticated(message: impl Into<String>) -> Self {
+        BackendError::new(StatusCode::UNAUTHORIZED, Code::Unauthenticated, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        BackendError::new(StatusCode::INTERNAL_SERVER_ERROR, Code::Internal, message)
+    }
+
+    /// Middleware can not be reached, the client is asked to retry later
+    pub fn unavailable(message: impl Into<String>) -> Self {
+        BackendError::new(StatusCode::SERVICE_UNAVAILABLE, Code::Unavailable, message).with_retry_after(RETRY_AFTER_SECONDS)
+    }
+
+    pub fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    pub fn with_retry_after(mut self, seconds: u64) -> Self {
+        self.retry_after = Some(seconds);
+        self
+    }
+}
+
+impl From<Status> for BackendError {
+    fn from(status: Status) -> Self {
+        if status.code() == Code::Unavailable {
+            return BackendError::unavailable(status.message());
+        }
+        //  tonic cancels the call when the timeout of the endpoint (`MIDDLEWARE_TIMEOUT`) expires
+        if status.code() == Code::Cancelled && is_timeout_expired(&status) {
+            return BackendError::new(StatusCode::GATEWAY_TIMEOUT, Code::DeadlineExceeded, "Middleware did not answer in time");
+        }
+        BackendError::new(http_status(status.code()), status.code(), status.message())
+    }
+}
+
+fn is_timeout_expired(status: &Status) -> bool {
+    let mut source = std::error::Error::source(status);
+    while let Some(error) = source {
+        if error.is::<TimeoutExpired>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.code, self.status, self.message)
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+#[async_trait]
+impl Writer for BackendError {
+    async fn write(mut self, req: &mut salvo::Request, _depot: &mut Depot, res: &mut Response) {
+        _depot.insert(ERROR_CODE_LABEL, self.code.clone());
+        let request_id = req.header::<String>(REQUEST_ID_HEADER).unwrap_or_else(|| Uuid::new_v4().to_string());
+        log::warn!("[{}] {}", request_id, self);
+        if let Ok(header_value) = HeaderValue::from_str(&request_id) {
+            res.headers_mut().insert(REQUEST_ID_HEADER, header_value);
+        }
+        if let Some(seconds) = self.retry_after {
+            res.headers_mut().insert(RETRY_AFTER, HeaderValue::from(seconds));
+        }
+        res.status_code(self.status);
+        res.render(Json(ErrorResponse {
+            code: self.code,
+            message: self.message,
+            details: self.details,
+            request_id
+        }));
+    }
+}
+
+/// HTTP status for a gRPC status code returned by adempiere-middleware
+pub fn http_status(code: Code) -> StatusCode {
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Cancelled | Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
+
+/// Canonical gRPC name of the code, as used in the `code` field of the envelope
+pub fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "OK",
+        Code::Cancelled => "CANCELLED",
+        Code::Unknown => "UNKNOWN",
+        Code::InvalidArgument => "INVALID_ARGUMENT",
+        Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+        Code::NotFound => "NOT_FOUND",
+        Code::AlreadyExists => "ALREADY_EXISTS",
+        Code::PermissionDenied => "PERMISSION_DENIED",
+        Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+        Code::FailedPrecondition => "FAILED_PRECONDITION",
+        Code::Aborted => "ABORTED",
+        Code::OutOfRange => "OUT_OF_RANGE",
+        Code::Unimplemented => "UNIMPLEMENTED",
+        Code::Internal => "INTERNAL",
+        Code::Unavailable => "UNAVAILABLE",
+        Code::DataLoss => "DATA_LOSS",
+        Code::Unauthenticated => "UNAUTHENTICATED"
+    }
+}
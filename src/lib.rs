@@ -1,4 +1,15 @@
+pub mod auth;
+pub mod channel;
+pub mod config;
+pub mod errors;
+pub mod metrics;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod models;
+pub mod sdk;
+pub mod server;
+pub mod tls;
+#[allow(clippy::tabs_in_doc_comments)]
 pub mod middleware {
     tonic::include_proto!("service");
 }
\ No newline at end of file
@@ -0,0 +1,209 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder};
+use salvo::http::header::{HeaderValue, CONTENT_TYPE};
+use salvo::prelude::*;
+use tonic::{Code, Status};
+
+use crate::errors::code_name;
+
+/// Prefix of every metric
+pub const NAMESPACE: &str = "adempiere_backend";
+/// Depot keys of the labels set by the handler when the request is sent to middleware
+pub const TABLE_NAME_LABEL: &str = "metrics_table_name";
+pub const PROCESS_CODE_LABEL: &str = "metrics_process_code";
+pub const ERROR_CODE_LABEL: &str = "metrics_error_code";
+/// Table name or process code of a request that failed, middleware did not confirm that it
+/// exists so a series is not created for each name sent by a client
+pub const OTHER_LABEL: &str = "other";
+
+/// Seconds of the latency buckets, up to a minute for long processes
+const LATENCY_BUCKETS: [f64; 13] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+struct Collectors {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    http_request_errors: IntCounterVec,
+    middleware_requests: IntCounterVec,
+    middleware_request_duration: HistogramVec,
+    middleware_connections: IntCounterVec,
+    middleware_reconnections: IntCounter
+}
+
+/// Prometheus metrics of the REST requests and of the calls to middleware, used as hoop
+/// of the router to time each request and rendered by `prometheus_metrics`
+#[derive(Clone)]
+pub struct Metrics {
+    collectors: Arc<Collectors>
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics::new()
+    }
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some(NAMESPACE.to_owned()), None).expect("valid namespace");
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "REST requests handled"),
+            &["method", "route", "status", "table_name", "process_code"]
+        ).expect("valid metric");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "Latency of the REST requests").buckets(LATENCY_BUCKETS.to_vec()),
+            &["method", "route", "status", "table_name", "process_code"]
+        ).expect("valid metric");
+        let http_request_errors = IntCounterVec::new(
+            Opts::new("http_request_errors_total", "REST requests answered with an error, by code of the error"),
+            &["method", "route", "status", "code", "table_name", "process_code"]
+        ).expect("valid metric");
+        let middleware_requests = IntCounterVec::new(
+            Opts::new("middleware_requests_total", "Calls to middleware, by gRPC method and code"),
+            &["method", "code"]
+        ).expect("valid metric");
+        let middleware_request_duration = HistogramVec::new(
+            HistogramOpts::new("middleware_request_duration_seconds", "Latency of the calls to middleware").buckets(LATENCY_BUCKETS.to_vec()),
+            &["method", "code"]
+        ).expect("valid metric");
+        let middleware_connections = IntCounterVec::new(
+            Opts::new("middleware_connections_total", "Connections of the channel to middleware, by result"),
+            &["result"]
+        ).expect("valid metric");
+        let middleware_reconnections = IntCounter::new("middleware_reconnections_total", "Connections of the channel to middleware after the first one").expect("valid metric");
+        registry.register(Box::new(http_requests.clone())).expect("metric registered");
+        registry.register(Box::new(http_request_duration.clone())).expect("metric registered");
+        registry.register(Box::new(http_request_errors.clone())).expect("metric registered");
+        registry.register(Box::new(middleware_requests.clone())).expect("metric registered");
+        registry.register(Box::new(middleware_request_duration.clone())).expect("metric registered");
+        registry.register(Box::new(middleware_connections.clone())).expect("metric registered");
+        registry.register(Box::new(middleware_reconnections.clone())).expect("metric registered");
+        Metrics {
+            collectors: Arc::new(Collectors {
+                registry,
+                http_requests,
+                http_request_duration,
+                http_request_errors,
+                middleware_requests,
+                middleware_request_duration,
+                middleware_connections,
+                middleware_reconnections
+            })
+        }
+    }
+
+    /// Text format of Prometheus
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(error) = TextEncoder::new().encode(&self.collectors.registry.gather(), &mut buffer) {
+            log::error!("Metrics can not be encoded: {}", error);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+
+    pub fn observe_middleware_call(&self, method: &str, code: Code, latency: Duration) {
+        let labels = [method, code_name(code)];
+        self.collectors.middleware_requests.with_label_values(&labels).inc();
+        self.collectors.middleware_request_duration.with_label_values(&labels).observe(latency.as_secs_f64());
+    }
+
+    /// Result of a connection of the channel to middleware
+    pub fn observe_connection(&self, connected: bool) {
+        if !connected {
+            self.collectors.middleware_connections.with_label_values(&["failure"]).inc();
+            return;
+        }
+        let connections = self.collectors.middleware_connections.with_label_values(&["success"]);
+        if connections.get() > 0 {
+            self.collectors.middleware_reconnections.inc();
+        }
+        connections.inc();
+    }
+
+    fn observe_request(&self, _req: &salvo::Request, _depot: &Depot, _res: &Response, latency: Duration) {
+        let status = _res.status_code.unwrap_or(StatusCode::OK);
+        let method = _req.method().as_str();
+        let route = route_label(_req);
+        let status_label = status.as_u16().to_string();
+        let table_name = name_label(_depot, TABLE_NAME_LABEL, status);
+        let process_code = name_label(_depot, PROCESS_CODE_LABEL, status);
+        let labels = [method, route.as_str(), status_label.as_str(), table_name, process_code];
+        self.collectors.http_requests.with_label_values(&labels).inc();
+        self.collectors.http_request_duration.with_label_values(&labels).observe(latency.as_secs_f64());
+        if status.is_client_error() || status.is_server_error() {
+            let code = _depot.get::<String>(ERROR_CODE_LABEL).map(|code| code.as_str()).unwrap_or_default();
+            self.collectors.http_request_errors.with_label_values(&[method, route.as_str(), status_label.as_str(), code, table_name, process_code]).inc();
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for Metrics {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let started = Instant::now();
+        _depot.inject(self.clone());
+        _ctrl.call_next(_req, _depot, _res).await;
+        self.observe_request(_req, _depot, _res, started.elapsed());
+    }
+}
+
+/// Path of the request with the parameters by name, e.g. `/v1/entities/<table_name>/<id>`,
+/// so a label is not created for each record
+fn route_label(_req: &salvo::Request) -> String {
+    let params = _req.params();
+    _req.uri().path().split('/').map(|segment| {
+        match params.iter().find(|(_, value)| !segment.is_empty() && value.as_str() == segment) {
+            Some((name, _)) => format!("<{}>", name),
+            None => segment.to_owned()
+        }
+    }).collect::<Vec<_>>().join("/")
+}
+
+/// Table name of a request that is sent to middleware, in lowercase as the names are not case sensitive
+pub(crate) fn label_table_name(_depot: &mut Depot, table_name: &str) {
+    _depot.insert(TABLE_NAME_LABEL, table_name.to_lowercase());
+}
+
+pub(crate) fn label_process_code(_depot: &mut Depot, process_code: &str) {
+    _depot.insert(PROCESS_CODE_LABEL, process_code.to_lowercase());
+}
+
+/// Name set by the handler when middleware answered successfully, else `other`
+fn name_label<'a>(_depot: &'a Depot, key: &str, status: StatusCode) -> &'a str {
+    match _depot.get::<String>(key) {
+        Some(name) if status.is_success() => name.as_str(),
+        Some(_) => OTHER_LABEL,
+        None => ""
+    }
+}
+
+/// Time a call to middleware with its gRPC code, when the router has metrics
+pub async fn observe_call<T>(_depot: &Depot, method: &str, call: impl Future<Output = Result<tonic::Response<T>, Status>>) -> Result<tonic::Response<T>, Status> {
+    let metrics = _depot.obtain::<Metrics>().cloned();
+    let started = Instant::now();
+    let result = call.await;
+    if let Some(metrics) = metrics {
+        let code = match &result {
+            Ok(_) => Code::Ok,
+            Err(status) => status.code()
+        };
+        metrics.observe_middleware_call(method, code, started.elapsed());
+    }
+    result
+}
+
+/// Metrics in the text format of Prometheus
+#[handler]
+pub async fn prometheus_metrics(_depot: &mut Depot, _res: &mut Response) {
+    let content = match _depot.obtain::<Metrics>() {
+        Some(metrics) => metrics.render(),
+        None => String::new()
+    };
+    _res.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static(prometheus::TEXT_FORMAT));
+    if let Err(error) = _res.write_body(content) {
+        log::error!("Metrics can not be written: {}", error);
+    }
+}
@@ -0,0 +1,442 @@
+//  tonic::Status is the error of every service method
+#![allow(clippy::result_large_err)]
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Deserialize;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{Request, Response, Status};
+use tonic::transport::Server;
+use tonic::transport::server::Router;
+use tonic_health::ServingStatus;
+
+use crate::middleware::{Condition, CreateEntityRequest, DeleteEntitiesRequest, DeleteEntitiesResponse, DeleteEntityRequest, DeleteEntityResult, Empty, Entity, GetEntityRequest, ListEntitiesRequest, ListEntitiesResponse, Operator, ProcessLog, RunBusinessProcessRequest, RunBusinessProcessResponse, UpdateEntityRequest, Value, ValueType};
+use crate::middleware::middleware_service_server::{MiddlewareService, MiddlewareServiceServer};
+use crate::models::decimal::from_grpc_decimal;
+
+/// First ID of each table, as the records created by a client in ADempiere
+pub const FIRST_ID: i32 = 1000000;
+
+/// Rules of the mock loaded from a JSON file: mandatory columns by table
+/// and the response of each process code
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct MockScript {
+    #[serde(default)]
+    pub mandatory: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub processes: HashMap<String, MockProcess>
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct MockProcess {
+    #[serde(default)]
+    pub is_error: bool,
+    pub summary: Option<String>,
+    pub result_table_name: Option<String>,
+    #[serde(default)]
+    pub logs: Vec<MockProcessLog>
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct MockProcessLog {
+    pub record_id: Option<i32>,
+    pub log: Option<String>
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    next_id: i32,
+    records: BTreeMap<i32, HashMap<String, Value>>
+}
+
+#[derive(Debug, Default)]
+struct Store {
+    tables: HashMap<String, Table>,
+    mandatory: HashMap<String, Vec<String>>,
+    processes: HashMap<String, RunBusinessProcessResponse>,
+    latency: Option<Duration>,
+    health_status: Option<ServingStatus>,
+    without_bulk_delete: bool
+}
+
+/// In-memory stand-in of adempiere-middleware, for tests and local development.
+/// Records are kept by table with auto-incremented IDs, calls without token are
+/// rejected and processes answer with the response registered for its code
+#[derive(Debug, Clone, Default)]
+pub struct MockMiddleware {
+    store: Arc<Mutex<Store>>
+}
+
+fn table_key(table_name: &str) -> String {
+    table_name.to_uppercase()
+}
+
+fn is_null(value: &Value) -> bool {
+    value.value_type == ValueType::Unknown as i32
+}
+
+/// Value as text, used for compare and sort
+fn value_text(value: &Value) -> String {
+    match ValueType::from_i32(value.value_type).unwrap_or(ValueType::Unknown) {
+        ValueType::Integer => value.integer_value.to_string(),
+        ValueType::Decimal => value.decimal_value.clone().map(from_grpc_decimal).unwrap_or_default(),
+        ValueType::Boolean => value.boolean_value.to_string(),
+        ValueType::String => value.string_value.clone(),
+        ValueType::Date => value.date_value.clone(),
+        ValueType::Unknown => String::new()
+    }
+}
+
+fn compare_text(left: &str, right: &str) -> Ordering {
+    match (left.parse::<f64>(), right.parse::<f64>()) {
+        (Ok(left), Ok(right)) => left.partial_cmp(&right).unwrap_or(Ordering::Equal),
+        _ => left.cmp(right)
+    }
+}
+
+/// SQL like pattern with `%` as wildcard, case insensitive
+fn is_like(value: &str, pattern: &str) -> bool {
+    let value = value.to_lowercase();
+    let parts: Vec<String> = pattern.to_lowercase().split('%').map(|part| part.to_owned()).collect();
+    if parts.len() == 1 {
+        return value == parts[0];
+    }
+    let mut position = 0;
+    for (index, part) in parts.iter().enumerate() {
+        if part.is_empty() {
+            continue;
+        }
+        if index == 0 {
+            if !value.starts_with(part.as_str()) {
+                return false;
+            }
+            position = part.len();
+        } else if index == parts.len() - 1 {
+            return value.len() >= position + part.len() && value.ends_with(part.as_str());
+        } else {
+            match value[position..].find(part.as_str()) {
+                Some(found) => position += found + part.len(),
+                None => return false
+            }
+        }
+    }
+    true
+}
+
+fn matches(values: &HashMap<String, Value>, condition: &Condition) -> bool {
+    let current = values.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(&condition.column_name))
+        .map(|(_, value)| value_text(value))
+        .unwrap_or_default();
+    let expected = condition.value.as_ref().map(value_text).unwrap_or_default();
+    let ordering = compare_text(&current, &expected);
+    match Operator::from_i32(condition.operator).unwrap_or(Operator::Equal) {
+        Operator::Equal => ordering == Ordering::Equal,
+        Operator::NotEqual => ordering != Ordering::Equal,
+        Operator::Less => ordering == Ordering::Less,
+        Operator::LessEqual => ordering != Ordering::Greater,
+        Operator::Greater => ordering == Ordering::Greater,
+        Operator::GreaterEqual => ordering != Ordering::Less,
+        Operator::Like => is_like(&current, &expected)
+    }
+}
+
+fn entity(table_name: &str, id: i32, values: &HashMap<String, Value>) -> Entity {
+    Entity {
+        id,
+        table_name: table_name.to_owned(),
+        values: values.clone()
+    }
+}
+
+fn not_found(table_name: &str, id: i32) -> Status {
+    Status::not_found(format!("Record {} not found for table {}", id, table_name))
+}
+
+fn authenticate<T>(request: &Request<T>) -> Result<(), Status> {
+    match request.metadata().get("authorization") {
+        Some(token) if !token.is_empty() => Ok(()),
+        _ => Err(Status::unauthenticated("Token is Mandatory"))
+    }
+}
+
+fn validate_table_name(table_name: &str) -> Result<(), Status> {
+    if table_name.trim().is_empty() {
+        return Err(Status::invalid_argument("Table Is Mandatory"));
+    }
+    Ok(())
+}
+
+impl MockMiddleware {
+    pub fn new() -> Self {
+        MockMiddleware::default()
+    }
+
+    pub fn from_script(script: MockScript) -> Self {
+        let mut mock = MockMiddleware::new();
+        for (table_name, columns) in script.mandatory {
+            mock = mock.with_mandatory(&table_name, &columns);
+        }
+        for (process_code, process) in script.processes {
+            mock = mock.with_process(&process_code, RunBusinessProcessResponse {
+                is_error: process.is_error,
+                summary: process.summary.unwrap_or_default(),
+                result_table_name: process.result_table_name.unwrap_or_default(),
+                logs: process.logs.into_iter().map(|log| ProcessLog {
+                    record_id: log.record_id.unwrap_or_default(),
+                    log: log.log.unwrap_or_default()
+                }).collect(),
+                ..Default::default()
+            });
+        }
+        mock
+    }
+
+    /// Columns that must have a value (not NULL) in each record of the table
+    pub fn with_mandatory<S: AsRef<str>>(self, table_name: &str, columns: &[S]) -> Self {
+        self.store.lock().unwrap().mandatory.insert(table_key(table_name), columns.iter().map(|column| column.as_ref().to_owned()).collect());
+        self
+    }
+
+    /// Response of a process code, any other code is not found
+    pub fn with_process(self, process_code: &str, response: RunBusinessProcessResponse) -> Self {
+        self.store.lock().unwrap().processes.insert(process_code.to_owned(), response);
+        self
+    }
+
+    /// Delay of every call, to simulate a slow middleware or a long process
+    pub fn with_latency(self, latency: Duration) -> Self {
+        self.store.lock().unwrap().latency = Some(latency);
+        self
+    }
+
+    /// Status answered by the gRPC Health protocol, `SERVING` when it is not set
+    pub fn with_health_status(self, status: ServingStatus) -> Self {
+        self.store.lock().unwrap().health_status = Some(status);
+        self
+    }
+
+    /// Answer `UNIMPLEMENTED` to `DeleteEntities`, as a middleware without bulk delete
+    pub fn without_bulk_delete(self) -> Self {
+        self.store.lock().unwrap().without_bulk_delete = true;
+        self
+    }
+
+    /// Values of a stored record, for assertions in tests
+    pub fn record(&self, table_name: &str, id: i32) -> Option<HashMap<String, Value>> {
+        self.store.lock().unwrap().tables.get(&table_key(table_name)).and_then(|table| table.records.get(&id).cloned())
+    }
+
+    pub fn record_count(&self, table_name: &str) -> usize {
+        self.store.lock().unwrap().tables.get(&table_key(table_name)).map(|table| table.records.len()).unwrap_or_default()
+    }
+
+    async fn wait_latency(&self) {
+        let latency = self.store.lock().unwrap().latency;
+        if let Some(latency) = latency {
+            tokio::time::sleep(latency).await;
+        }
+    }
+
+    pub fn into_service(self) -> MiddlewareServiceServer<MockMiddleware> {
+        MiddlewareServiceServer::new(self)
+    }
+
+    /// Server of the mock and of the gRPC Health protocol
+    async fn router(self) -> Router {
+        let (mut reporter, health_service) = tonic_health::server::health_reporter();
+        let health_status = self.store.lock().unwrap().health_status;
+        if let Some(status) = health_status {
+            reporter.set_service_status("", status).await;
+        }
+        Server::builder().add_service(health_service).add_service(self.into_service())
+    }
+
+    pub async fn serve(self, address: SocketAddr) -> Result<(), tonic::transport::Error> {
+        self.router().await.serve(address).await
+    }
+
+    /// Serve on a free local port in background, the address is returned for connect to it
+    pub async fn spawn(self) -> std::io::Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        tokio::spawn(self.router().await.serve_with_incoming(TcpListenerStream::new(listener)));
+        Ok(address)
+    }
+}
+
+impl Store {
+    fn check_mandatory(&self, table_name: &str, values: &HashMap<String, Value>) -> Result<(), Status> {
+        let missing: Vec<&String> = self.mandatory.get(&table_key(table_name))
+            .map(|columns| columns.iter().filter(|column| !values.iter().any(|(key, value)| key.eq_ignore_ascii_case(column) && !is_null(value))).collect())
+            .unwrap_or_default();
+        if !missing.is_empty() {
+            return Err(Status::invalid_argument(format!("Mandatory columns without value: {}", missing.iter().map(|column| column.as_str()).collect::<Vec<_>>().join(", "))));
+        }
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl MiddlewareService for MockMiddleware {
+    async fn create_entity(&self, request: Request<CreateEntityRequest>) -> Result<Response<Entity>, Status> {
+        authenticate(&request)?;
+        self.wait_latency().await;
+        let request = request.into_inner();
+        validate_table_name(&request.table_name)?;
+        let values: HashMap<String, Value> = request.attributes.into_iter()
+            .filter_map(|attribute| attribute.value.map(|value| (attribute.key, value)))
+            .filter(|(_, value)| !is_null(value))
+            .collect();
+        let mut store = self.store.lock().unwrap();
+        store.check_mandatory(&request.table_name, &values)?;
+        let table = store.tables.entry(table_key(&request.table_name)).or_insert_with(|| Table {
+            next_id: FIRST_ID,
+            ..Default::default()
+        });
+        let id = table.next_id;
+        table.next_id += 1;
+        table.records.insert(id, values.clone());
+        Ok(Response::new(entity(&request.table_name, id, &values)))
+    }
+
+    async fn update_entity(&self, request: Request<UpdateEntityRequest>) -> Result<Response<Entity>, Status> {
+        authenticate(&request)?;
+        self.wait_latency().await;
+        let request = request.into_inner();
+        validate_table_name(&request.table_name)?;
+        let mut store = self.store.lock().unwrap();
+        let mut values = match store.tables.get(&table_key(&request.table_name)).and_then(|table| table.records.get(&request.id)) {
+            Some(values) => values.clone(),
+            None => return Err(not_found(&request.table_name, request.id))
+        };
+        for attribute in request.attributes {
+            match attribute.value {
+                Some(value) if !is_null(&value) => {
+                    values.insert(attribute.key, value);
+                },
+                _ => {
+                    values.remove(&attribute.key);
+                }
+            }
+        }
+        store.check_mandatory(&request.table_name, &values)?;
+        if let Some(table) = store.tables.get_mut(&table_key(&request.table_name)) {
+            table.records.insert(request.id, values.clone());
+        }
+        Ok(Response::new(entity(&request.table_name, request.id, &values)))
+    }
+
+    async fn delete_entity(&self, request: Request<DeleteEntityRequest>) -> Result<Response<Empty>, Status> {
+        authenticate(&request)?;
+        self.wait_latency().await;
+        let request = request.into_inner();
+        validate_table_name(&request.table_name)?;
+        let mut store = self.store.lock().unwrap();
+        match store.tables.get_mut(&table_key(&request.table_name)).and_then(|table| table.records.remove(&request.id)) {
+            Some(_) => Ok(Response::new(Empty {})),
+            None => Err(not_found(&request.table_name, request.id))
+        }
+    }
+
+    async fn delete_entities(&self, request: Request<DeleteEntitiesRequest>) -> Result<Response<DeleteEntitiesResponse>, Status> {
+        authenticate(&request)?;
+        if self.store.lock().unwrap().without_bulk_delete {
+            return Err(Status::unimplemented("DeleteEntities is not implemented"));
+        }
+        self.wait_latency().await;
+        let request = request.into_inner();
+        validate_table_name(&request.table_name)?;
+        let mut store = self.store.lock().unwrap();
+        //  Each record is deleted on its own, a record not found does not stop the others.
+        //  A table without records is not created, it would start with ID 0
+        let mut table = store.tables.get_mut(&table_key(&request.table_name));
+        let results = request.ids.into_iter().map(|id| match table.as_mut().and_then(|table| table.records.remove(&id)) {
+            Some(_) => DeleteEntityResult {
+                id,
+                is_deleted: true,
+                ..Default::default()
+            },
+            None => {
+                let status = not_found(&request.table_name, id);
+                DeleteEntityResult {
+                    id,
+                    is_deleted: false,
+                    code: status.code() as i32,
+                    message: status.message().to_owned()
+                }
+            }
+        }).collect();
+        Ok(Response::new(DeleteEntitiesResponse {
+            results
+        }))
+    }
+
+    async fn get_entity(&self, request: Request<GetEntityRequest>) -> Result<Response<Entity>, Status> {
+        authenticate(&request)?;
+        self.wait_latency().await;
+        let request = request.into_inner();
+        validate_table_name(&request.table_name)?;
+        let store = self.store.lock().unwrap();
+        match store.tables.get(&table_key(&request.table_name)).and_then(|table| table.records.get(&request.id)) {
+            Some(values) => Ok(Response::new(entity(&request.table_name, request.id, values))),
+            None => Err(not_found(&request.table_name, request.id))
+        }
+    }
+
+    async fn list_entities(&self, request: Request<ListEntitiesRequest>) -> Result<Response<ListEntitiesResponse>, Status> {
+        authenticate(&request)?;
+        self.wait_latency().await;
+        let request = request.into_inner();
+        validate_table_name(&request.table_name)?;
+        let store = self.store.lock().unwrap();
+        let mut records: Vec<(i32, &HashMap<String, Value>)> = store.tables.get(&table_key(&request.table_name))
+            .map(|table| table.records.iter()
+                .filter(|(_, values)| request.conditions.iter().all(|condition| matches(values, condition)))
+                .map(|(id, values)| (*id, values))
+                .collect())
+            .unwrap_or_default();
+        //  `Name ASC, Created DESC`
+        let sort_by: Vec<(String, bool)> = request.sort_by.split(',')
+            .filter_map(|column| {
+                let mut parts = column.split_whitespace();
+                parts.next().map(|column_name| (column_name.to_owned(), parts.next().map(|direction| direction.eq_ignore_ascii_case("DESC")).unwrap_or(false)))
+            })
+            .collect();
+        records.sort_by(|(left_id, left), (right_id, right)| {
+            sort_by.iter()
+                .map(|(column_name, is_descending)| {
+                    let text = |values: &HashMap<String, Value>| values.iter().find(|(key, _)| key.eq_ignore_ascii_case(column_name)).map(|(_, value)| value_text(value)).unwrap_or_default();
+                    let ordering = compare_text(&text(left), &text(right));
+                    if *is_descending { ordering.reverse() } else { ordering }
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or_else(|| left_id.cmp(right_id))
+        });
+        let record_count = records.len();
+        let offset = request.page_token.parse::<usize>().unwrap_or_default();
+        let page_size = if request.page_size > 0 { request.page_size as usize } else { record_count };
+        let next_offset = offset + page_size;
+        Ok(Response::new(ListEntitiesResponse {
+            record_count: record_count as i64,
+            records: records.into_iter().skip(offset).take(page_size).map(|(id, values)| entity(&request.table_name, id, values)).collect(),
+            next_page_token: if next_offset < record_count { next_offset.to_string() } else { String::new() }
+        }))
+    }
+
+    async fn run_business_process(&self, request: Request<RunBusinessProcessRequest>) -> Result<Response<RunBusinessProcessResponse>, Status> {
+        authenticate(&request)?;
+        self.wait_latency().await;
+        let request = request.into_inner();
+        let store = self.store.lock().unwrap();
+        match store.processes.get(&request.process_code) {
+            Some(response) => Ok(Response::new(response.clone())),
+            None => Err(Status::not_found(format!("Process {} not found", request.process_code)))
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, SecondsFormat, TimeZone};
+
+/// Format of dates sent to middleware, in the time zone of the server (`TZ`)
+pub const MIDDLEWARE_DATE_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+pub const MIDDLEWARE_DATE_FORMAT: &str = "%Y-%m-%d";
+
+const NAIVE_DATE_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M"
+];
+
+/// Date or date time written by a client or returned by middleware
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateValue {
+    Date(NaiveDate),
+    DateTime(DateTime<FixedOffset>)
+}
+
+/// Parse an ISO-8601 date (`2026-01-31`) or date time, with offset
+/// (`2026-01-31T10:00:00-04:00`) or without it, then read in the default time zone
+pub fn parse_date(value: &str) -> Option<DateValue> {
+    parse_date_in(value, &Local)
+}
+
+/// `parse_date` in the time zone `timezone`, the date time is converted to it
+pub fn parse_date_in<Tz: TimeZone>(value: &str, timezone: &Tz) -> Option<DateValue> {
+    let value = value.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(value, MIDDLEWARE_DATE_FORMAT) {
+        return Some(DateValue::Date(date));
+    }
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Some(DateValue::DateTime(date_time.with_timezone(timezone).fixed_offset()));
+    }
+    NAIVE_DATE_TIME_FORMATS.iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|date_time| match timezone.from_local_datetime(&date_time) {
+            LocalResult::Single(date_time) => Some(date_time),
+            //  Daylight saving time overlap, first occurrence is used
+            LocalResult::Ambiguous(date_time, _) => Some(date_time),
+            LocalResult::None => None
+        })
+        .map(|date_time| DateValue::DateTime(date_time.fixed_offset()))
+}
+
+/// Date from a client normalized to the format of middleware, `None` if it is not a valid date
+pub fn to_grpc_date(value: &str) -> Option<String> {
+    to_grpc_date_in(value, &Local)
+}
+
+pub fn to_grpc_date_in<Tz: TimeZone>(value: &str, timezone: &Tz) -> Option<String> {
+    parse_date_in(value, timezone).map(|date_value| match date_value {
+        DateValue::Date(date) => date.format(MIDDLEWARE_DATE_FORMAT).to_string(),
+        DateValue::DateTime(date_time) => date_time.format(MIDDLEWARE_DATE_TIME_FORMAT).to_string()
+    })
+}
+
+/// Date from middleware rendered as ISO-8601 with the offset of the default time zone,
+/// epoch milliseconds are also accepted. Unknown formats are returned as is
+pub fn from_grpc_date(value: String) -> String {
+    from_grpc_date_in(value, &Local)
+}
+
+pub fn from_grpc_date_in<Tz: TimeZone>(value: String, timezone: &Tz) -> String {
+    let date_value = match value.parse::<i64>() {
+        Ok(milliseconds) => timezone.timestamp_millis_opt(milliseconds).single().map(|date_time| DateValue::DateTime(date_time.fixed_offset())),
+        Err(_) => parse_date_in(&value, timezone)
+    };
+    match date_value {
+        Some(DateValue::Date(date)) => date.format(MIDDLEWARE_DATE_FORMAT).to_string(),
+        Some(DateValue::DateTime(date_time)) => date_time.to_rfc3339_opts(SecondsFormat::Secs, false),
+        None => value
+    }
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Deserializer};
+use serde::de::Error;
+use serde_json::Value;
+
+use crate::middleware::Decimal;
+
+/// Accept a decimal as JSON string (exact) or JSON number and keep it as string
+pub fn deserialize_decimal<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.trim().to_owned())),
+        Some(Value::Number(value)) => Ok(Some(number_to_string(&value))),
+        Some(value) => Err(D::Error::custom(format!("invalid decimal value: {}", value)))
+    }
+}
+
+/// Plain notation of a JSON number, without exponent
+pub fn number_to_string(value: &serde_json::Number) -> String {
+    match value.as_f64() {
+        Some(float_value) if value.is_f64() => float_value.to_string(),
+        _ => value.to_string()
+    }
+}
+
+/// Digits after the decimal point, `None` if the value is not a plain decimal number
+pub fn decimal_scale(value: &str) -> Option<i32> {
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    if !integer.chars().all(|digit| digit.is_ascii_digit()) || !fraction.chars().all(|digit| digit.is_ascii_digit()) {
+        return None;
+    }
+    Some(fraction.len() as i32)
+}
+
+/// Build the gRPC decimal keeping the value as it was written
+pub fn to_grpc_decimal(value: &str) -> Option<Decimal> {
+    decimal_scale(value).map(|scale| Decimal {
+        decimal_value: value.to_owned(),
+        scale
+    })
+}
+
+/// Decimal from middleware as string, padded with zeros up to its scale
+pub fn from_grpc_decimal(decimal: Decimal) -> String {
+    let mut value = decimal.decimal_value;
+    if let Some(current_scale) = decimal_scale(&value) {
+        if current_scale < decimal.scale {
+            if current_scale == 0 && !value.ends_with('.') {
+                value.push('.');
+            }
+            value.push_str(&"0".repeat((decimal.scale - current_scale) as usize));
+        }
+    }
+    value
+}
@@ -1,44 +1,72 @@
-use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde::de::{MapAccess, SeqAccess, Visitor};
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
 use salvo::prelude::*;
+use utoipa::ToSchema;
+
+use serde_json::{json, Map};
 
-use crate::middleware::{KeyValue, Value, ValueType, Entity, Decimal, RunBusinessProcessResponse};
+use crate::errors::{code_name, BackendError};
+use crate::middleware::{KeyValue, KeyValueSelection, Value, ValueType, Entity, DeleteEntityResult, ListEntitiesResponse, RunBusinessProcessResponse};
+use crate::models::date::{from_grpc_date, parse_date, to_grpc_date};
+use crate::models::decimal::{decimal_scale, deserialize_decimal, from_grpc_decimal, number_to_string, to_grpc_decimal};
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, Default, ToSchema)]
 pub struct EntityResponse {
     pub table_name: Option <String>,
     pub id: Option<i32>,
-    pub attributes: Option<Vec<KeyAndValue>>
+    pub attributes: Option<Attributes>
 
 }
 
-impl Default for EntityResponse {
-    fn default() -> Self {
+impl EntityResponse {
+    pub fn from_entity(source: Entity) -> Self {
         EntityResponse {
-            table_name: None,
-            id: None,
-            attributes: None
+            table_name: Some(source.table_name),
+            id: Some(source.id),
+            attributes: Some(Attributes::List(source.values.iter().map(|(key, value)| KeyAndValue::from_grpc_value(key.clone(), value.clone()))
+            .collect::<Vec<KeyAndValue>>()))
         }
     }
+
+    /// Render the attributes as a JSON object, `{"Name": "Standard", ...}`
+    pub fn into_flat(mut self) -> Self {
+        self.attributes = self.attributes.map(Attributes::into_object);
+        self
+    }
 }
 
-impl EntityResponse {
-    pub fn from_entity(source: Entity) -> Self {
-        let mut entity_response = EntityResponse::default();
-        entity_response.table_name = Some(source.table_name);
-        entity_response.id = Some(source.id);
-        entity_response.attributes = Some(source.values.iter().map(|(key, value)| KeyAndValue::from_grpc_value(key.clone(), value.clone()))
-        .collect::<Vec<KeyAndValue>>());
-        entity_response
+#[derive(Serialize, Debug, Clone, Default, ToSchema)]
+pub struct EntitiesResponse {
+    pub record_count: Option<i64>,
+    pub next_page_token: Option<String>,
+    pub records: Option<Vec<EntityResponse>>
+}
+
+impl EntitiesResponse {
+    pub fn from_list_response(source: ListEntitiesResponse) -> Self {
+        EntitiesResponse {
+            record_count: Some(source.record_count),
+            next_page_token: Some(source.next_page_token),
+            records: Some(source.records.into_iter().map(EntityResponse::from_entity).collect())
+        }
+    }
+
+    pub fn into_flat(mut self) -> Self {
+        self.records = self.records.map(|records| records.into_iter().map(EntityResponse::into_flat).collect());
+        self
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, ToSchema)]
 pub struct ProcessLog {
     pub record_id: Option<i32>,
     pub log: Option<String>
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Debug, Clone, Default, ToSchema)]
 pub struct ProcessResponse {
     pub id: Option<i32>,
     pub is_error: Option<bool>,
@@ -49,47 +77,35 @@ pub struct ProcessResponse {
     pub logs: Option<Vec<ProcessLog>>
 }
 
-impl Default for ProcessResponse {
-    fn default() -> Self {
-        ProcessResponse {
-            id: None,
-            is_error: None,
-            summary: None,
-            result_table_name: None,
-            is_processing: None,
-            last_run: None,
-            logs: None
-        }
-    }
-}
-
 impl ProcessResponse {
     pub fn from_process_response(source: RunBusinessProcessResponse) -> Self {
-        let mut process_response = ProcessResponse::default();
-        process_response.id = Some(source.id);
-        process_response.is_error = Some(source.is_error);
-        process_response.summary = Some(source.summary);
-        process_response.result_table_name = Some(source.result_table_name);
-        process_response.is_processing = Some(source.is_processing);
-        process_response.last_run = Some(source.last_run);
-        process_response.logs = Some(source.logs.iter().map(|log_value| {
-            ProcessLog {
-                record_id: Some(log_value.to_owned().record_id),
-                log: Some(log_value.to_owned().log)
-            }
-        }).collect());
-        process_response
+        ProcessResponse {
+            id: Some(source.id),
+            is_error: Some(source.is_error),
+            summary: Some(source.summary),
+            result_table_name: Some(source.result_table_name),
+            is_processing: Some(source.is_processing),
+            last_run: Some(source.last_run),
+            logs: Some(source.logs.iter().map(|log_value| {
+                ProcessLog {
+                    record_id: Some(log_value.to_owned().record_id),
+                    log: Some(log_value.to_owned().log)
+                }
+            }).collect())
+        }
     }
 }
 
-#[derive(Deserialize, Serialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Serialize, Extractible, Debug, Clone, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct KeyAndValue {
     pub key: String,
     pub integer_value: Option<i32>,
     pub boolean_value: Option<bool>,
     pub string_value: Option<String>,
     pub date_value: Option<String>,
-    pub decimal_value: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_decimal")]
+    pub decimal_value: Option<String>,
     pub value_type: Option<String> 
 }
 
@@ -108,38 +124,231 @@ impl Default for KeyAndValue {
     }
 }
 
+/// Attributes of an entity, as a list of `KeyAndValue` or as a plain JSON object
+/// where the value type is inferred from the JSON type
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[serde(untagged)]
+pub enum Attributes {
+    List(Vec<KeyAndValue>),
+    Object(#[schema(value_type = Object)] Map<String, serde_json::Value>)
+}
+
+/// The variant is chosen by the JSON type, so the error of a `KeyAndValue`
+/// (e.g. an unknown field) is reported instead of the one of an untagged enum
+impl<'de> Deserialize<'de> for Attributes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AttributesVisitor;
+
+        impl<'de> Visitor<'de> for AttributesVisitor {
+            type Value = Attributes;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a list of key and value or a JSON object")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Attributes, A::Error> {
+                Vec::deserialize(SeqAccessDeserializer::new(seq)).map(Attributes::List)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Attributes, A::Error> {
+                Map::deserialize(MapAccessDeserializer::new(map)).map(Attributes::Object)
+            }
+        }
+
+        deserializer.deserialize_any(AttributesVisitor)
+    }
+}
+
+impl Attributes {
+    pub fn into_key_values(self) -> Result<Vec<KeyAndValue>, BackendError> {
+        match self {
+            Attributes::List(values) => Ok(values),
+            Attributes::Object(values) => values.into_iter().map(|(key, value)| KeyAndValue::from_json_value(key, value)).collect()
+        }
+    }
+
+    pub fn into_object(self) -> Self {
+        match self {
+            Attributes::List(values) => Attributes::Object(values.iter().map(|value| (value.key.clone(), value.to_json_value())).collect()),
+            Attributes::Object(values) => Attributes::Object(values)
+        }
+    }
+}
+
+/// Value type used on REST for a column without value (`NULL` on database)
+pub const NULL_VALUE_TYPE: &str = "NULL";
+
+/// How the value type of a `KeyAndValue` is resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConversionMode {
+    /// Only one value field is allowed and it must match `value_type` when it is sent
+    #[default]
+    Strict,
+    /// First value field found is used, checking integer, decimal, boolean, string and date in that order
+    Lenient
+}
+
 impl KeyAndValue {
-    pub fn to_grpc_value(self) -> KeyValue {
+    /// Value fields sent for this key with the type that each one represents
+    pub fn value_fields(&self) -> Vec<(&'static str, ValueType)> {
+        let mut fields = Vec::new();
+        if self.integer_value.is_some() {
+            fields.push(("integer_value", ValueType::Integer));
+        }
+        if self.decimal_value.is_some() {
+            fields.push(("decimal_value", ValueType::Decimal));
+        }
+        if self.boolean_value.is_some() {
+            fields.push(("boolean_value", ValueType::Boolean));
+        }
+        if self.string_value.is_some() {
+            fields.push(("string_value", ValueType::String));
+        }
+        if self.date_value.is_some() {
+            fields.push(("date_value", ValueType::Date));
+        }
+        fields
+    }
+
+    pub fn has_value(&self) -> bool {
+        !self.value_fields().is_empty()
+    }
+
+    /// Only an explicit `"value_type": "NULL"`, an attribute without value is rejected
+    pub fn is_null(&self) -> bool {
+        match &self.value_type {
+            Some(value_type) => value_type.eq_ignore_ascii_case(NULL_VALUE_TYPE),
+            None => false
+        }
+    }
+
+    /// An attribute that is not NULL requires a value field, also with `value_type`
+    fn check_has_value(&self) -> Result<(), BackendError> {
+        if self.has_value() {
+            return Ok(());
+        }
+        match &self.value_type {
+            Some(value_type) => Err(BackendError::invalid_argument(format!("Value Type {} requires a value for key {}", value_type, self.key))
+                .with_details(json!({"key": self.key, "value_type": value_type}))),
+            None => Err(BackendError::invalid_argument(format!("Value is Mandatory for key {}, use value_type NULL to clear it", self.key))
+                .with_details(json!({"key": self.key})))
+        }
+    }
+
+    fn strict_value_type(&self) -> Result<ValueType, BackendError> {
+        let fields = self.value_fields();
+        if fields.len() > 1 {
+            let field_names: Vec<&str> = fields.iter().map(|(field_name, _)| *field_name).collect();
+            return Err(BackendError::invalid_argument(format!("Only one value is allowed for key {}", self.key)).with_details(json!({"key": self.key, "fields": field_names})));
+        }
+        let (field_name, field_type) = fields[0];
+        match &self.value_type {
+            None => Ok(field_type),
+            Some(value_type) => match ValueType::from_str_name(&value_type.to_uppercase()) {
+                Some(expected_type) if expected_type == field_type => Ok(field_type),
+                Some(expected_type) if expected_type != ValueType::Unknown => Err(BackendError::invalid_argument(format!("Value Type {} does not match {} for key {}", value_type, field_name, self.key))
+                    .with_details(json!({"key": self.key, "value_type": value_type, "field": field_name}))),
+                _ => Err(BackendError::invalid_argument(format!("Invalid Value Type {} for key {}", value_type, self.key)).with_details(json!({"key": self.key, "value_type": value_type})))
+            }
+        }
+    }
+
+    pub fn to_grpc_value(self) -> Result<KeyValue, BackendError> {
+        self.to_grpc_value_with_mode(ConversionMode::Strict)
+    }
+
+    pub fn to_grpc_value_with_mode(self, mode: ConversionMode) -> Result<KeyValue, BackendError> {
         let mut value = Value {
             ..Default::default()
         };
-        if self.integer_value.is_some() {
-            value.integer_value = self.integer_value.unwrap_or_default();
-            value.value_type = ValueType::Integer.to_owned().into();
-        } else if self.decimal_value.is_some() {
-            let numeric_value = self.decimal_value.unwrap();
-            let string_value = numeric_value.to_string();
-            let decimal_index = string_value.find(".");
-            let precision = (string_value.len() - 1) - decimal_index.unwrap_or_default();
-            value.decimal_value = Some(Decimal {
-                decimal_value: numeric_value.to_string(),
-                scale: precision as i32
-            });
-            value.value_type = ValueType::Decimal.to_owned().into();
-        } else if self.boolean_value.is_some() {
-            value.boolean_value = self.boolean_value.unwrap_or_default();
-            value.value_type = ValueType::Boolean.to_owned().into();
-        } else if self.string_value.is_some() {
-            value.string_value = self.string_value.unwrap_or_default();
-            value.value_type = ValueType::String.to_owned().into();
-        } else if self.date_value.is_some() {
-            value.date_value = self.date_value.unwrap_or_default();
-            value.value_type = ValueType::Date.to_owned().into();
-        }
-        KeyValue {
+        //  Middleware reads a value with UNKNOWN type as NULL
+        if self.is_null() {
+            if self.has_value() {
+                return Err(BackendError::invalid_argument("NULL Value can not have a value").with_details(json!({"key": self.key})));
+            }
+            value.value_type = ValueType::Unknown.into();
+        } else {
+            self.check_has_value()?;
+            let value_type = match mode {
+                ConversionMode::Strict => self.strict_value_type()?,
+                ConversionMode::Lenient => self.value_fields()[0].1
+            };
+            match value_type {
+                ValueType::Integer => value.integer_value = self.integer_value.unwrap_or_default(),
+                ValueType::Decimal => {
+                    let decimal_value = self.decimal_value.clone().unwrap_or_default();
+                    value.decimal_value = to_grpc_decimal(&decimal_value);
+                    if value.decimal_value.is_none() {
+                        return Err(BackendError::invalid_argument("Invalid Decimal Value").with_details(json!({"key": self.key, "decimal_value": decimal_value})));
+                    }
+                },
+                ValueType::Boolean => value.boolean_value = self.boolean_value.unwrap_or_default(),
+                ValueType::String => value.string_value = self.string_value.clone().unwrap_or_default(),
+                ValueType::Date => {
+                    let date_value = self.date_value.clone().unwrap_or_default();
+                    value.date_value = match to_grpc_date(&date_value) {
+                        Some(date_value) => date_value,
+                        None => return Err(BackendError::invalid_argument("Invalid Date Value, an ISO-8601 date is expected").with_details(json!({"key": self.key, "date_value": date_value})))
+                    };
+                },
+                ValueType::Unknown => {}
+            }
+            value.value_type = value_type.into();
+        }
+        Ok(KeyValue {
             key: self.key, 
             value: Some(value) 
+        })
+    }
+
+    /// Value of a plain JSON object: `null` is NULL, booleans are BOOLEAN, integer numbers
+    /// are INTEGER (DECIMAL out of the integer range), other numbers are DECIMAL. Strings with
+    /// an ISO-8601 date are DATE, strings with a number with decimal point are DECIMAL and
+    /// any other string is STRING
+    pub fn from_json_value(key: String, value: serde_json::Value) -> Result<KeyAndValue, BackendError> {
+        let mut value_to_convert = KeyAndValue {
+            key,
+            ..Default::default()
+        };
+        match value {
+            serde_json::Value::Null => value_to_convert.value_type = Some(NULL_VALUE_TYPE.to_string()),
+            serde_json::Value::Bool(boolean_value) => value_to_convert.boolean_value = Some(boolean_value),
+            serde_json::Value::Number(number) => match number.as_i64().and_then(|integer_value| i32::try_from(integer_value).ok()) {
+                Some(integer_value) => value_to_convert.integer_value = Some(integer_value),
+                None => value_to_convert.decimal_value = Some(number_to_string(&number))
+            },
+            serde_json::Value::String(string_value) => {
+                if parse_date(&string_value).is_some() {
+                    value_to_convert.date_value = Some(string_value);
+                } else if string_value.contains('.') && decimal_scale(&string_value).is_some() {
+                    value_to_convert.decimal_value = Some(string_value);
+                } else {
+                    value_to_convert.string_value = Some(string_value);
+                }
+            },
+            _ => return Err(BackendError::invalid_argument(format!("Unsupported Value for key {}", value_to_convert.key)).with_details(json!({"key": value_to_convert.key})))
         }
+        Ok(value_to_convert)
+    }
+
+    /// Value for a plain JSON object, decimals are kept as string
+    pub fn to_json_value(&self) -> serde_json::Value {
+        if let Some(integer_value) = self.integer_value {
+            return json!(integer_value);
+        }
+        if let Some(decimal_value) = &self.decimal_value {
+            return json!(decimal_value);
+        }
+        if let Some(boolean_value) = self.boolean_value {
+            return json!(boolean_value);
+        }
+        if let Some(string_value) = &self.string_value {
+            return json!(string_value);
+        }
+        if let Some(date_value) = &self.date_value {
+            return json!(date_value);
+        }
+        serde_json::Value::Null
     }
 
     pub fn from_grpc_value(key: String, value: Value) -> KeyAndValue {
@@ -151,14 +360,7 @@ impl KeyAndValue {
             value_to_convert.integer_value = Some(value.integer_value);
             value_to_convert.value_type = Some(ValueType::Integer.as_str_name().to_string());
         } else if value.value_type() == ValueType::Decimal {
-            if value.decimal_value.is_some() {
-                value_to_convert.decimal_value = match value.decimal_value {
-                    Some(decimal_value) => {
-                        Some(decimal_value.decimal_value.parse::<f32>().unwrap().into())
-                    },
-                    None => None
-                };
-            }
+            value_to_convert.decimal_value = value.decimal_value.map(from_grpc_decimal);
             value_to_convert.value_type = Some(ValueType::Decimal.as_str_name().to_string());
         } else if value.value_type() == ValueType::Boolean {
             value_to_convert.boolean_value = Some(value.boolean_value);
@@ -167,60 +369,147 @@ impl KeyAndValue {
             value_to_convert.string_value = Some(value.string_value);
             value_to_convert.value_type = Some(ValueType::String.as_str_name().to_string());
         } else if value.value_type() == ValueType::Date {
-            value_to_convert.date_value = Some(value.date_value);
+            value_to_convert.date_value = Some(from_grpc_date(value.date_value));
             value_to_convert.value_type = Some(ValueType::Date.as_str_name().to_string());
+        } else {
+            value_to_convert.value_type = Some(NULL_VALUE_TYPE.to_string());
         }
         value_to_convert
     }
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 pub struct EntityNew {
     pub table_name: Option<String>,
-    pub attributes: Option<Vec<KeyAndValue>>
+    pub attributes: Option<Attributes>
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 #[extract(default_source(from = "body", format = "json"))]
 pub struct EntityNewDocument {
     pub entity: Option<EntityNew>
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+/// Body of the entity routes that take the table (and ID) from the path
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
+#[extract(default_source(from = "body", format = "json"))]
+pub struct EntityAttributesDocument {
+    pub attributes: Option<Attributes>
+}
+
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 pub struct EntityUpdate {
     pub table_name: Option<String>,
     pub id: Option<i32>,
-    pub attributes: Option<Vec<KeyAndValue>>
+    pub attributes: Option<Attributes>
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 #[extract(default_source(from = "body", format = "json"))]
 pub struct EntityUpdateDocument {
     pub entity: Option<EntityUpdate>
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 pub struct EntityDelete {
     pub table_name: Option<String>,
     pub id: Option<i32>
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 #[extract(default_source(from = "body", format = "json"))]
 pub struct EntityDeleteDocument {
     pub entity: Option<EntityDelete>
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
+pub struct EntitiesDelete {
+    pub table_name: Option<String>,
+    pub ids: Option<Vec<i32>>
+}
+
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
+#[extract(default_source(from = "body", format = "json"))]
+pub struct EntitiesDeleteDocument {
+    pub entities: Option<EntitiesDelete>
+}
+
+#[derive(Serialize, Debug, Clone, Default, ToSchema)]
+pub struct EntityDeleteResult {
+    pub id: Option<i32>,
+    pub is_deleted: Option<bool>,
+    pub code: Option<String>,
+    pub message: Option<String>
+}
+
+impl EntityDeleteResult {
+    pub fn from_result(result: DeleteEntityResult) -> Self {
+        if result.is_deleted {
+            return EntityDeleteResult {
+                id: Some(result.id),
+                is_deleted: Some(true),
+                ..Default::default()
+            };
+        }
+        EntityDeleteResult {
+            id: Some(result.id),
+            is_deleted: Some(false),
+            code: Some(code_name(tonic::Code::from_i32(result.code)).to_owned()),
+            message: Some(result.message)
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default, ToSchema)]
+pub struct EntitiesDeleteResponse {
+    pub table_name: Option<String>,
+    pub deleted: Option<i32>,
+    pub failed: Option<i32>,
+    pub results: Option<Vec<EntityDeleteResult>>
+}
+
+impl EntitiesDeleteResponse {
+    pub fn from_results(table_name: String, results: Vec<EntityDeleteResult>) -> Self {
+        let deleted = results.iter().filter(|result| result.is_deleted.unwrap_or_default()).count() as i32;
+        EntitiesDeleteResponse {
+            table_name: Some(table_name),
+            deleted: Some(deleted),
+            failed: Some(results.len() as i32 - deleted),
+            results: Some(results)
+        }
+    }
+}
+
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 pub struct RunProcess {
     pub table_name: Option<String>,
     pub id: Option<i32>,
     pub process_code: Option<String>,
     pub table_selected_id: Option<i32>,
-    pub parameters: Option<Vec<KeyAndValue>>
+    pub parameters: Option<Vec<KeyAndValue>>,
+    pub selections: Option<Vec<KeyAndValueSelection>>
+}
+
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
+pub struct KeyAndValueSelection {
+    pub selection_id: Option<i32>,
+    pub values: Option<Vec<KeyAndValue>>
+}
+
+impl KeyAndValueSelection {
+    pub fn to_grpc_value(self) -> Result<KeyValueSelection, BackendError> {
+        self.to_grpc_value_with_mode(ConversionMode::Strict)
+    }
+
+    pub fn to_grpc_value_with_mode(self, mode: ConversionMode) -> Result<KeyValueSelection, BackendError> {
+        Ok(KeyValueSelection {
+            selection_id: self.selection_id.unwrap_or_default(),
+            values: self.values.unwrap_or_default().into_iter().map(|value| value.to_grpc_value_with_mode(mode)).collect::<Result<Vec<KeyValue>, BackendError>>()?
+        })
+    }
 }
 
-#[derive(Deserialize, Extractible, Debug, Clone)]
+#[derive(Deserialize, Extractible, Debug, Clone, ToSchema)]
 #[extract(default_source(from = "body", format = "json"))]
 pub struct RunProcessDocument {
     pub process: Option<RunProcess>
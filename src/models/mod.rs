@@ -1 +1,4 @@
-pub mod documents;
\ No newline at end of file
+pub mod date;
+pub mod decimal;
+pub mod documents;
+pub mod query;
\ No newline at end of file
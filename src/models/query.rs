@@ -0,0 +1,88 @@
+use serde_json::json;
+
+use crate::errors::BackendError;
+use crate::middleware::{Condition, Operator, Value, ValueType};
+
+pub const DEFAULT_PAGE_SIZE: i32 = 50;
+pub const MAX_PAGE_SIZE: i32 = 500;
+
+/// Query parameters used by the list endpoint itself, any other one is a column filter
+pub const RESERVED_PARAMETERS: [&str; 5] = ["sort_by", "page_size", "page_token", "lenient", "format"];
+
+/// Column names are sent to middleware as part of the query, only identifiers are allowed
+pub fn is_valid_column_name(column_name: &str) -> bool {
+    !column_name.is_empty() && column_name.chars().all(|character| character.is_ascii_alphanumeric() || character == '_')
+}
+
+fn operator_from_name(name: &str) -> Option<Operator> {
+    match name.to_lowercase().as_str() {
+        "eq" => Some(Operator::Equal),
+        "ne" => Some(Operator::NotEqual),
+        "lt" => Some(Operator::Less),
+        "le" => Some(Operator::LessEqual),
+        "gt" => Some(Operator::Greater),
+        "ge" => Some(Operator::GreaterEqual),
+        "like" => Some(Operator::Like),
+        _ => None
+    }
+}
+
+/// Filter from a query parameter, `Name=Standard` compares by equal and
+/// `Created[ge]=2026-01-01` uses one of `eq`, `ne`, `lt`, `le`, `gt`, `ge` or `like`.
+/// Values are sent as string, middleware converts them using the column type
+pub fn condition_from_parameter(key: &str, value: &str) -> Result<Condition, BackendError> {
+    let (column_name, operator) = match key.strip_suffix(']').and_then(|key| key.split_once('[')) {
+        Some((column_name, operator_name)) => match operator_from_name(operator_name) {
+            Some(operator) => (column_name, operator),
+            None => return Err(BackendError::invalid_argument(format!("Invalid Operator {}", operator_name)).with_details(json!({"parameter": key})))
+        },
+        None => (key, Operator::Equal)
+    };
+    if !is_valid_column_name(column_name) {
+        return Err(BackendError::invalid_argument(format!("Invalid Column Name {}", column_name)).with_details(json!({"parameter": key})));
+    }
+    Ok(Condition {
+        column_name: column_name.to_owned(),
+        operator: operator.into(),
+        value: Some(Value {
+            string_value: value.to_owned(),
+            value_type: ValueType::String.into(),
+            ..Default::default()
+        })
+    })
+}
+
+pub fn conditions_from_parameters(parameters: Vec<(String, String)>) -> Result<Vec<Condition>, BackendError> {
+    parameters.iter()
+        .filter(|(key, _)| !RESERVED_PARAMETERS.contains(&key.as_str()))
+        .map(|(key, value)| condition_from_parameter(key, value))
+        .collect()
+}
+
+/// `Name,-Created` to `Name ASC, Created DESC`
+pub fn sort_by_from_parameter(value: &str) -> Result<String, BackendError> {
+    value.split(',')
+        .map(|column| column.trim())
+        .filter(|column| !column.is_empty())
+        .map(|column| {
+            let (column_name, direction) = match column.strip_prefix('-') {
+                Some(column_name) => (column_name, "DESC"),
+                None => (column.strip_prefix('+').unwrap_or(column), "ASC")
+            };
+            if !is_valid_column_name(column_name) {
+                return Err(BackendError::invalid_argument(format!("Invalid Sort Column {}", column_name)).with_details(json!({"parameter": "sort_by"})));
+            }
+            Ok(format!("{} {}", column_name, direction))
+        })
+        .collect::<Result<Vec<String>, BackendError>>()
+        .map(|columns| columns.join(", "))
+}
+
+/// `max_page_size` is `limits.max_page_size` of the configuration
+pub fn page_size_from_parameter(value: Option<i32>, max_page_size: i32) -> Result<i32, BackendError> {
+    match value {
+        None => Ok(DEFAULT_PAGE_SIZE.min(max_page_size)),
+        Some(page_size) if page_size > 0 && page_size <= max_page_size => Ok(page_size),
+        Some(_) => Err(BackendError::invalid_argument(format!("Page Size must be between 1 and {}", max_page_size)).with_details(json!({"parameter": "page_size"})))
+    }
+}
@@ -0,0 +1,420 @@
+//! Typed client of adempiere-middleware for Rust services
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), adempiere_backend_rs::sdk::ClientError> {
+//! use adempiere_backend_rs::sdk::AdempiereClient;
+//!
+//! let client = AdempiereClient::builder()
+//!     .endpoint("http://0.0.0.0:50059")
+//!     .token("Bearer <Token>")
+//!     .build()?;
+//! let entity = client.create("C_BPartner")
+//!     .set("Name", "Test from Rust")
+//!     .set("IsDefault", false)
+//!     .send()
+//!     .await?;
+//! client.delete("C_BPartner", entity.id.unwrap_or_default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use tonic::{Code, Status};
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::ClientTlsConfig;
+
+use crate::channel::{middleware_client, middleware_endpoint, MiddlewareClient};
+use crate::errors::code_name;
+use crate::middleware::{CreateEntityRequest, DeleteEntityRequest, GetEntityRequest, KeyValue, KeyValueSelection, RunBusinessProcessRequest, UpdateEntityRequest};
+use crate::models::date::{MIDDLEWARE_DATE_FORMAT, MIDDLEWARE_DATE_TIME_FORMAT};
+use crate::models::documents::{EntityResponse, KeyAndValue, ProcessResponse, NULL_VALUE_TYPE};
+
+/// Error of a call, local validations are reported before anything is sent
+#[derive(Debug, Clone)]
+pub enum ClientError {
+    /// Endpoint is missing or it is not a valid URI
+    InvalidEndpoint(String),
+    /// Token is missing or it is not a valid header value
+    InvalidToken,
+    /// Attribute or parameter that can not be converted to a middleware value
+    InvalidValue { key: String, message: String },
+    /// Status returned by middleware, or by tonic when middleware can not be reached
+    Status { code: Code, message: String }
+}
+
+impl ClientError {
+    /// gRPC code of the error, `INVALID_ARGUMENT` for local validations
+    pub fn code(&self) -> Code {
+        match self {
+            ClientError::InvalidEndpoint(_) | ClientError::InvalidValue { .. } => Code::InvalidArgument,
+            ClientError::InvalidToken => Code::Unauthenticated,
+            ClientError::Status { code, .. } => *code
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.code() == Code::NotFound
+    }
+
+    /// Middleware can not be reached, the call can be retried
+    pub fn is_unavailable(&self) -> bool {
+        self.code() == Code::Unavailable
+    }
+}
+
+impl From<Status> for ClientError {
+    fn from(status: Status) -> Self {
+        ClientError::Status {
+            code: status.code(),
+            message: status.message().to_owned()
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::InvalidEndpoint(message) => write!(f, "Invalid Endpoint: {}", message),
+            ClientError::InvalidToken => write!(f, "Invalid Token"),
+            ClientError::InvalidValue { key, message } => write!(f, "Invalid Value for key {}: {}", key, message),
+            ClientError::Status { code, message } => write!(f, "{}: {}", code_name(*code), message)
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+/// Value of an attribute or parameter, built from Rust types with `into()`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Null,
+    Integer(i32),
+    Boolean(bool),
+    String(String),
+    /// Exact decimal written as string, e.g. `"10.50"`
+    Decimal(String),
+    /// ISO-8601 date or date time
+    Date(String)
+}
+
+impl AttributeValue {
+    /// Amounts and quantities are written as string, there is no conversion from `f64` as it is not exact
+    pub fn decimal(value: impl Into<String>) -> Self {
+        AttributeValue::Decimal(value.into())
+    }
+
+    pub fn date(value: impl Into<String>) -> Self {
+        AttributeValue::Date(value.into())
+    }
+
+    fn into_key_and_value(self, key: String) -> KeyAndValue {
+        let mut value = KeyAndValue {
+            key,
+            ..Default::default()
+        };
+        match self {
+            AttributeValue::Null => value.value_type = Some(NULL_VALUE_TYPE.to_owned()),
+            AttributeValue::Integer(integer_value) => value.integer_value = Some(integer_value),
+            AttributeValue::Boolean(boolean_value) => value.boolean_value = Some(boolean_value),
+            AttributeValue::String(string_value) => value.string_value = Some(string_value),
+            AttributeValue::Decimal(decimal_value) => value.decimal_value = Some(decimal_value),
+            AttributeValue::Date(date_value) => value.date_value = Some(date_value)
+        }
+        value
+    }
+}
+
+impl From<i32> for AttributeValue {
+    fn from(value: i32) -> Self {
+        AttributeValue::Integer(value)
+    }
+}
+
+impl From<bool> for AttributeValue {
+    fn from(value: bool) -> Self {
+        AttributeValue::Boolean(value)
+    }
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+
+impl From<NaiveDate> for AttributeValue {
+    fn from(value: NaiveDate) -> Self {
+        AttributeValue::Date(value.format(MIDDLEWARE_DATE_FORMAT).to_string())
+    }
+}
+
+impl From<NaiveDateTime> for AttributeValue {
+    fn from(value: NaiveDateTime) -> Self {
+        AttributeValue::Date(value.format(MIDDLEWARE_DATE_TIME_FORMAT).to_string())
+    }
+}
+
+impl From<DateTime<Local>> for AttributeValue {
+    fn from(value: DateTime<Local>) -> Self {
+        AttributeValue::Date(value.to_rfc3339())
+    }
+}
+
+impl<T: Into<AttributeValue>> From<Option<T>> for AttributeValue {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => AttributeValue::Null
+        }
+    }
+}
+
+fn to_grpc_values(values: Vec<(String, AttributeValue)>) -> Result<Vec<KeyValue>, ClientError> {
+    values.into_iter()
+        .map(|(key, value)| value.into_key_and_value(key.clone()).to_grpc_value().map_err(|error| ClientError::InvalidValue {
+            key,
+            message: error.message
+        }))
+        .collect()
+}
+
+/// Builder of [`AdempiereClient`]
+#[derive(Debug, Clone, Default)]
+pub struct AdempiereClientBuilder {
+    endpoint: Option<String>,
+    token: Option<String>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    tls: Option<ClientTlsConfig>
+}
+
+impl AdempiereClientBuilder {
+    /// Address of adempiere-middleware, e.g. `http://0.0.0.0:50059`
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Token sent in the `authorization` metadata, the `Bearer ` prefix is added when missing
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Timeout of each call
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// TLS configuration, for an `https://` endpoint
+    pub fn tls(mut self, tls: ClientTlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Client connected on first call, the connection is shared by its clones
+    pub fn build(self) -> Result<AdempiereClient, ClientError> {
+        let endpoint = match self.endpoint {
+            Some(endpoint) => endpoint,
+            None => return Err(ClientError::InvalidEndpoint("Endpoint is Mandatory".to_owned()))
+        };
+        let token = match self.token {
+            Some(token) if token.starts_with("Bearer ") => token,
+            Some(token) if !token.is_empty() => format!("Bearer {}", token),
+            _ => return Err(ClientError::InvalidToken)
+        };
+        let token: MetadataValue<Ascii> = token.parse().map_err(|_| ClientError::InvalidToken)?;
+        let mut endpoint = middleware_endpoint(endpoint).map_err(|error| ClientError::InvalidEndpoint(error.to_string()))?;
+        if let Some(connect_timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(connect_timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            endpoint = endpoint.timeout(timeout);
+        }
+        if let Some(tls) = self.tls {
+            endpoint = endpoint.tls_config(tls).map_err(|error| ClientError::InvalidEndpoint(error.to_string()))?;
+        }
+        Ok(AdempiereClient {
+            client: middleware_client(endpoint.connect_lazy(), token)
+        })
+    }
+}
+
+/// Client of adempiere-middleware, cheap to clone
+#[derive(Clone)]
+pub struct AdempiereClient {
+    client: MiddlewareClient
+}
+
+impl AdempiereClient {
+    pub fn builder() -> AdempiereClientBuilder {
+        AdempiereClientBuilder::default()
+    }
+
+    /// New record of a table, attributes are added with `set`
+    pub fn create(&self, table_name: impl Into<String>) -> CreateEntity {
+        CreateEntity {
+            client: self.client.clone(),
+            table_name: table_name.into(),
+            attributes: Vec::new()
+        }
+    }
+
+    /// Change attributes of a record, attributes are added with `set`
+    pub fn update(&self, table_name: impl Into<String>, id: i32) -> UpdateEntity {
+        UpdateEntity {
+            client: self.client.clone(),
+            table_name: table_name.into(),
+            id,
+            attributes: Vec::new()
+        }
+    }
+
+    pub async fn get(&self, table_name: impl Into<String>, id: i32) -> Result<EntityResponse, ClientError> {
+        let response = self.client.clone().get_entity(tonic::Request::new(GetEntityRequest {
+            table_name: table_name.into(),
+            id
+        })).await?;
+        Ok(EntityResponse::from_entity(response.into_inner()))
+    }
+
+    pub async fn delete(&self, table_name: impl Into<String>, id: i32) -> Result<(), ClientError> {
+        self.client.clone().delete_entity(tonic::Request::new(DeleteEntityRequest {
+            table_name: table_name.into(),
+            id
+        })).await?;
+        Ok(())
+    }
+
+    /// Run a process or report by its code, parameters are added with `parameter`
+    pub fn run_process(&self, process_code: impl Into<String>) -> RunProcess {
+        RunProcess {
+            client: self.client.clone(),
+            process_code: process_code.into(),
+            table_name: String::new(),
+            id: 0,
+            table_selected_id: 0,
+            parameters: Vec::new(),
+            selections: Vec::new()
+        }
+    }
+}
+
+#[must_use = "the entity is created by send"]
+pub struct CreateEntity {
+    client: MiddlewareClient,
+    table_name: String,
+    attributes: Vec<(String, AttributeValue)>
+}
+
+impl CreateEntity {
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        self.attributes.push((key.into(), value.into()));
+        self
+    }
+
+    pub async fn send(mut self) -> Result<EntityResponse, ClientError> {
+        let attributes = to_grpc_values(self.attributes)?;
+        let response = self.client.create_entity(tonic::Request::new(CreateEntityRequest {
+            table_name: self.table_name,
+            attributes
+        })).await?;
+        Ok(EntityResponse::from_entity(response.into_inner()))
+    }
+}
+
+#[must_use = "the entity is updated by send"]
+pub struct UpdateEntity {
+    client: MiddlewareClient,
+    table_name: String,
+    id: i32,
+    attributes: Vec<(String, AttributeValue)>
+}
+
+impl UpdateEntity {
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        self.attributes.push((key.into(), value.into()));
+        self
+    }
+
+    pub async fn send(mut self) -> Result<EntityResponse, ClientError> {
+        let attributes = to_grpc_values(self.attributes)?;
+        let response = self.client.update_entity(tonic::Request::new(UpdateEntityRequest {
+            table_name: self.table_name,
+            id: self.id,
+            attributes
+        })).await?;
+        Ok(EntityResponse::from_entity(response.into_inner()))
+    }
+}
+
+#[must_use = "the process is run by send"]
+pub struct RunProcess {
+    client: MiddlewareClient,
+    process_code: String,
+    table_name: String,
+    id: i32,
+    table_selected_id: i32,
+    parameters: Vec<(String, AttributeValue)>,
+    selections: Vec<(i32, Vec<(String, AttributeValue)>)>
+}
+
+impl RunProcess {
+    /// Record the process is run from
+    pub fn record(mut self, table_name: impl Into<String>, id: i32) -> Self {
+        self.table_name = table_name.into();
+        self.id = id;
+        self
+    }
+
+    pub fn parameter(mut self, key: impl Into<String>, value: impl Into<AttributeValue>) -> Self {
+        self.parameters.push((key.into(), value.into()));
+        self
+    }
+
+    /// Table of the selected records, see `selection`
+    pub fn table_selected_id(mut self, table_selected_id: i32) -> Self {
+        self.table_selected_id = table_selected_id;
+        self
+    }
+
+    /// Record selected for the process with its values, e.g. a line of a smart browser
+    pub fn selection<K: Into<String>, V: Into<AttributeValue>>(mut self, selection_id: i32, values: impl IntoIterator<Item = (K, V)>) -> Self {
+        self.selections.push((selection_id, values.into_iter().map(|(key, value)| (key.into(), value.into())).collect()));
+        self
+    }
+
+    pub async fn send(mut self) -> Result<ProcessResponse, ClientError> {
+        let parameters = to_grpc_values(self.parameters)?;
+        let selections = self.selections.into_iter()
+            .map(|(selection_id, values)| to_grpc_values(values).map(|values| KeyValueSelection {
+                selection_id,
+                values
+            }))
+            .collect::<Result<Vec<KeyValueSelection>, ClientError>>()?;
+        let response = self.client.run_business_process(tonic::Request::new(RunBusinessProcessRequest {
+            process_code: self.process_code,
+            table_name: self.table_name,
+            id: self.id,
+            table_selected_id: self.table_selected_id,
+            parameters,
+            selections
+        })).await?;
+        Ok(ProcessResponse::from_process_response(response.into_inner()))
+    }
+}
@@ -0,0 +1,429 @@
+use salvo::prelude::*;
+use salvo::http::header::{HeaderValue, ALLOW, LOCATION};
+use serde_json::json;
+use tonic::Code;
+
+use crate::channel::middleware_client;
+use crate::errors::{code_name, BackendError};
+use crate::metrics::{label_table_name, observe_call};
+use crate::middleware::{KeyValue, CreateEntityRequest, UpdateEntityRequest, DeleteEntityRequest, DeleteEntitiesRequest, GetEntityRequest, ListEntitiesRequest};
+use crate::models::documents::{EntityAttributesDocument, EntityNewDocument, EntityResponse, EntitiesResponse, EntityUpdateDocument, EntityDeleteDocument, EntitiesDeleteDocument, EntitiesDeleteResponse, EntityDeleteResult, Attributes};
+use crate::models::query::{conditions_from_parameters, page_size_from_parameter, sort_by_from_parameter};
+use crate::server::{authorization_token, conversion_mode, id_param, is_flat_format, limits, middleware_channel, parse_document, render_entity, table_name_param};
+
+async fn send_create_entity(_req: &salvo::Request, _depot: &mut Depot, table_name: String, attributes: Attributes) -> Result<EntityResponse, BackendError> {
+    let token = authorization_token(_req)?;
+    let mode = conversion_mode(_req);
+    let attributes: Vec<KeyValue> = attributes.into_key_values()?.into_iter().map(|value| value.to_grpc_value_with_mode(mode)).collect::<Result<_, _>>()?;
+    label_table_name(_depot, &table_name);
+    let mut client = middleware_client(middleware_channel(_depot)?, token);
+    match observe_call(_depot, "CreateEntity", client.create_entity(tonic::Request::new(CreateEntityRequest {
+        table_name,
+        attributes
+    }))).await {
+        Ok(response) => {
+            let entity = response.into_inner();
+            log::info!("{}", entity.id);
+            Ok(EntityResponse::from_entity(entity))
+        }, 
+        Err(error) => Err(error.into())
+    }
+}
+
+async fn send_update_entity(_req: &salvo::Request, _depot: &mut Depot, table_name: String, id: i32, attributes: Attributes) -> Result<EntityResponse, BackendError> {
+    let token = authorization_token(_req)?;
+    let mode = conversion_mode(_req);
+    let attributes: Vec<KeyValue> = attributes.into_key_values()?.into_iter().map(|value| value.to_grpc_value_with_mode(mode)).collect::<Result<_, _>>()?;
+    label_table_name(_depot, &table_name);
+    let mut client = middleware_client(middleware_channel(_depot)?, token);
+    match observe_call(_depot, "UpdateEntity", client.update_entity(tonic::Request::new(UpdateEntityRequest {
+        table_name,
+        id,
+        attributes
+    }))).await {
+        Ok(response) => {
+            let entity = response.into_inner();
+            log::info!("{}", entity.id);
+            Ok(EntityResponse::from_entity(entity))
+        }, 
+        Err(error) => Err(error.into())
+    }
+}
+
+async fn send_delete_entity(_req: &salvo::Request, _depot: &mut Depot, table_name: String, id: i32) -> Result<(), BackendError> {
+    let token = authorization_token(_req)?;
+    label_table_name(_depot, &table_name);
+    let mut client = middleware_client(middleware_channel(_depot)?, token);
+    match observe_call(_depot, "DeleteEntity", client.delete_entity(tonic::Request::new(DeleteEntityRequest {
+        table_name,
+        id
+    }))).await {
+        Ok(_) => {
+            log::info!("{}", id);
+            Ok(())
+        }, 
+        Err(error) => Err(error.into())
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/entities",
+    tag = "Entities",
+    request_body = EntityNewDocument,
+    params(
+        ("lenient" = Option<bool>, Query, description = "Use the first value found instead of validating value_type"),
+        ("format" = Option<String>, Query, description = "`flat` renders attributes as a JSON object")
+    ),
+    responses(
+        (status = 200, description = "Entity created", body = EntityResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn create_entity<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let _document: EntityNewDocument = parse_document(_req).await?;
+    let _entity = _document.entity;
+    if _entity.is_none() {
+        return Err(BackendError::invalid_argument("Entity Is Mandatory").with_details(json!({"field": "entity"})));
+    }
+    let _entity = _entity.unwrap();
+    if _entity.table_name.is_none() {
+        return Err(BackendError::invalid_argument("Table Is Mandatory").with_details(json!({"field": "table_name"})));
+    }
+    if _entity.attributes.is_none() {
+        return Err(BackendError::invalid_argument("Attributes are Mandatory").with_details(json!({"field": "attributes"})));
+    }
+    let entity = send_create_entity(_req, _depot, _entity.table_name.unwrap(), _entity.attributes.unwrap()).await?;
+    _res.status_code(StatusCode::OK);
+    render_entity(_req, _res, entity);
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/entities/{table_name}",
+    tag = "Entities",
+    request_body = EntityAttributesDocument,
+    params(
+        ("table_name" = String, Path, description = "Table Name, e.g. M_Product_Class"),
+        ("lenient" = Option<bool>, Query, description = "Use the first value found instead of validating value_type"),
+        ("format" = Option<String>, Query, description = "`flat` renders attributes as a JSON object")
+    ),
+    responses(
+        (status = 201, description = "Entity created", body = EntityResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn create_table_entity<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let _document: EntityAttributesDocument = parse_document(_req).await?;
+    let table_name = table_name_param(_req)?;
+    if _document.attributes.is_none() {
+        return Err(BackendError::invalid_argument("Attributes are Mandatory").with_details(json!({"field": "attributes"})));
+    }
+    let entity = send_create_entity(_req, _depot, table_name.clone(), _document.attributes.unwrap()).await?;
+    if let Ok(location) = HeaderValue::from_str(&format!("/v1/entities/{}/{}", table_name, entity.id.unwrap_or_default())) {
+        _res.headers_mut().insert(LOCATION, location);
+    }
+    _res.status_code(StatusCode::CREATED);
+    render_entity(_req, _res, entity);
+    Ok(())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/entities",
+    tag = "Entities",
+    request_body = EntityUpdateDocument,
+    params(
+        ("lenient" = Option<bool>, Query, description = "Use the first value found instead of validating value_type"),
+        ("format" = Option<String>, Query, description = "`flat` renders attributes as a JSON object")
+    ),
+    responses(
+        (status = 200, description = "Entity updated", body = EntityResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn update_entity<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let _document: EntityUpdateDocument = parse_document(_req).await?;
+    let _entity = _document.entity;
+    if _entity.is_none() {
+        return Err(BackendError::invalid_argument("Entity Is Mandatory").with_details(json!({"field": "entity"})));
+    }
+    let _entity = _entity.unwrap();
+    if _entity.table_name.is_none() {
+        return Err(BackendError::invalid_argument("Table Is Mandatory").with_details(json!({"field": "table_name"})));
+    }
+    if _entity.attributes.is_none() {
+        return Err(BackendError::invalid_argument("Attributes are Mandatory").with_details(json!({"field": "attributes"})));
+    }
+    if _entity.id.is_none() {
+        return Err(BackendError::invalid_argument("ID is Mandatory").with_details(json!({"field": "id"})));
+    }
+    let entity = send_update_entity(_req, _depot, _entity.table_name.unwrap(), _entity.id.unwrap(), _entity.attributes.unwrap()).await?;
+    _res.status_code(StatusCode::OK);
+    render_entity(_req, _res, entity);
+    Ok(())
+}
+
+#[utoipa::path(
+    patch,
+    path = "/v1/entities/{table_name}/{id}",
+    tag = "Entities",
+    request_body = EntityAttributesDocument,
+    params(
+        ("table_name" = String, Path, description = "Table Name, e.g. M_Product_Class"),
+        ("id" = i32, Path, description = "Record ID"),
+        ("lenient" = Option<bool>, Query, description = "Use the first value found instead of validating value_type"),
+        ("format" = Option<String>, Query, description = "`flat` renders attributes as a JSON object")
+    ),
+    responses(
+        (status = 200, description = "Entity updated", body = EntityResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn update_table_entity<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let _document: EntityAttributesDocument = parse_document(_req).await?;
+    let table_name = table_name_param(_req)?;
+    let id = id_param(_req)?;
+    if _document.attributes.is_none() {
+        return Err(BackendError::invalid_argument("Attributes are Mandatory").with_details(json!({"field": "attributes"})));
+    }
+    let entity = send_update_entity(_req, _depot, table_name, id, _document.attributes.unwrap()).await?;
+    _res.status_code(StatusCode::OK);
+    render_entity(_req, _res, entity);
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/entities/{table_name}/{id}",
+    tag = "Entities",
+    params(
+        ("table_name" = String, Path, description = "Table Name, e.g. M_Product_Class"),
+        ("id" = i32, Path, description = "Record ID"),
+        ("format" = Option<String>, Query, description = "`flat` renders attributes as a JSON object")
+    ),
+    responses(
+        (status = 200, description = "Entity found", body = EntityResponse),
+        (status = 404, description = "Entity not found", body = ErrorResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn get_entity<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let table_name = table_name_param(_req)?;
+    let id = id_param(_req)?;
+    let token = authorization_token(_req)?;
+    label_table_name(_depot, &table_name);
+    let mut client = middleware_client(middleware_channel(_depot)?, token);
+    match observe_call(_depot, "GetEntity", client.get_entity(tonic::Request::new(GetEntityRequest {
+        table_name,
+        id
+    }))).await {
+        Ok(response) => {
+            let entity = response.get_ref();
+            _res.status_code(StatusCode::OK);
+            render_entity(_req, _res, EntityResponse::from_entity(entity.to_owned()));
+            Ok(())
+        }, 
+        Err(error) => Err(error.into())
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/entities/{table_name}",
+    tag = "Entities",
+    params(
+        ("table_name" = String, Path, description = "Table Name, e.g. M_Product_Class"),
+        ("sort_by" = Option<String>, Query, description = "Columns separated by comma, a `-` before the column sorts descending"),
+        ("page_size" = Option<i32>, Query, description = "Records by page, default 50 and max 500 (`limits.max_page_size`)"),
+        ("page_token" = Option<String>, Query, description = "`next_page_token` of the previous page"),
+        ("format" = Option<String>, Query, description = "`flat` renders attributes as a JSON object")
+    ),
+    responses(
+        (status = 200, description = "Entities found, any other query parameter is a column filter (`Column=value` or `Column[operator]=value`)", body = EntitiesResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn list_entities<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let table_name = table_name_param(_req)?;
+    let parameters: Vec<(String, String)> = _req.queries().iter().map(|(key, value)| (key.to_owned(), value.to_owned())).collect();
+    let conditions = conditions_from_parameters(parameters)?;
+    let sort_by = sort_by_from_parameter(&_req.query::<String>("sort_by").unwrap_or_default())?;
+    let page_size = page_size_from_parameter(_req.query::<i32>("page_size"), limits(_depot).max_page_size)?;
+    let token = authorization_token(_req)?;
+    label_table_name(_depot, &table_name);
+    let mut client = middleware_client(middleware_channel(_depot)?, token);
+    match observe_call(_depot, "ListEntities", client.list_entities(tonic::Request::new(ListEntitiesRequest {
+        table_name,
+        conditions,
+        sort_by,
+        page_size,
+        page_token: _req.query::<String>("page_token").unwrap_or_default()
+    }))).await {
+        Ok(response) => {
+            let entities = response.into_inner();
+            log::info!("Records: {}", entities.record_count);
+            _res.status_code(StatusCode::OK);
+            let entities = EntitiesResponse::from_list_response(entities);
+            if is_flat_format(_req) {
+                _res.render(Json(entities.into_flat()));
+            } else {
+                _res.render(Json(entities));
+            }
+            Ok(())
+        }, 
+        Err(error) => Err(error.into())
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/entities",
+    tag = "Entities",
+    request_body = EntityDeleteDocument,
+    responses(
+        (status = 200, description = "Entity deleted", body = String),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn delete_entity<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let _document: EntityDeleteDocument = parse_document(_req).await?;
+    let _entity = _document.entity;
+    if _entity.is_none() {
+        return Err(BackendError::invalid_argument("Entity Is Mandatory").with_details(json!({"field": "entity"})));
+    }
+    let _entity = _entity.unwrap();
+    if _entity.table_name.is_none() {
+        return Err(BackendError::invalid_argument("Table Is Mandatory").with_details(json!({"field": "table_name"})));
+    }
+    if _entity.id.is_none() {
+        return Err(BackendError::invalid_argument("ID is Mandatory").with_details(json!({"field": "id"})));
+    }
+    send_delete_entity(_req, _depot, _entity.table_name.unwrap(), _entity.id.unwrap()).await?;
+    _res.status_code(StatusCode::OK);
+    _res.render(Json("Ok"));
+    Ok(())
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/entities/{table_name}/{id}",
+    tag = "Entities",
+    params(
+        ("table_name" = String, Path, description = "Table Name, e.g. M_Product_Class"),
+        ("id" = i32, Path, description = "Record ID")
+    ),
+    responses(
+        (status = 204, description = "Entity deleted"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn delete_table_entity<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let table_name = table_name_param(_req)?;
+    let id = id_param(_req)?;
+    send_delete_entity(_req, _depot, table_name, id).await?;
+    _res.status_code(StatusCode::NO_CONTENT);
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/entities/delete",
+    tag = "Entities",
+    request_body = EntitiesDeleteDocument,
+    responses(
+        (status = 200, description = "Result of delete for each ID", body = EntitiesDeleteResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn delete_entities<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let _document: EntitiesDeleteDocument = parse_document(_req).await?;
+    let _entities = _document.entities;
+    if _entities.is_none() {
+        return Err(BackendError::invalid_argument("Entities Is Mandatory").with_details(json!({"field": "entities"})));
+    }
+    let _entities = _entities.unwrap();
+    if _entities.table_name.is_none() {
+        return Err(BackendError::invalid_argument("Table Is Mandatory").with_details(json!({"field": "table_name"})));
+    }
+    let ids = _entities.ids.unwrap_or_default();
+    if ids.is_empty() {
+        return Err(BackendError::invalid_argument("IDs are Mandatory").with_details(json!({"field": "ids"})));
+    }
+    let token = authorization_token(_req)?;
+    let table_name = _entities.table_name.unwrap();
+    label_table_name(_depot, &table_name);
+    let mut client = middleware_client(middleware_channel(_depot)?, token);
+    let results = match observe_call(_depot, "DeleteEntities", client.delete_entities(tonic::Request::new(DeleteEntitiesRequest {
+        table_name: table_name.clone(),
+        ids: ids.clone()
+    }))).await {
+        Ok(response) => response.into_inner().results.into_iter().map(EntityDeleteResult::from_result).collect(),
+        //  Middleware without bulk delete support, delete one by one
+        Err(error) if error.code() == Code::Unimplemented => {
+            log::info!("DeleteEntities not supported by middleware, deleting {} records one by one", ids.len());
+            let mut results = Vec::with_capacity(ids.len());
+            for id in ids {
+                let result = observe_call(_depot, "DeleteEntity", client.delete_entity(tonic::Request::new(DeleteEntityRequest {
+                    table_name: table_name.clone(),
+                    id
+                }))).await;
+                results.push(match result {
+                    Ok(_) => EntityDeleteResult {
+                        id: Some(id),
+                        is_deleted: Some(true),
+                        ..Default::default()
+                    },
+                    Err(error) => EntityDeleteResult {
+                        id: Some(id),
+                        is_deleted: Some(false),
+                        code: Some(code_name(error.code()).to_owned()),
+                        message: Some(error.message().to_owned())
+                    }
+                });
+            }
+            results
+        },
+        Err(error) => return Err(error.into())
+    };
+    let response = EntitiesDeleteResponse::from_results(table_name, results);
+    log::info!("Deleted: {:?} Failed: {:?}", response.deleted, response.failed);
+    _res.status_code(StatusCode::OK);
+    _res.render(Json(response));
+    Ok(())
+}
+
+/// Other methods of `v1/entities/delete`, so they are not sent to a table named `delete`
+#[handler]
+pub async fn delete_entities_not_allowed(_res: &mut Response) -> Result<(), BackendError> {
+    _res.headers_mut().insert(ALLOW, HeaderValue::from_static("POST"));
+    Err(BackendError::new(StatusCode::METHOD_NOT_ALLOWED, Code::Unimplemented, "Method Not Allowed").with_details(json!({"allow": "POST"})))
+}
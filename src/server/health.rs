@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+
+use salvo::prelude::*;
+use serde::Serialize;
+use tonic::Code;
+use tonic::transport::Channel;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+use utoipa::ToSchema;
+
+use crate::errors::code_name;
+use crate::server::middleware_channel;
+
+/// How `health/ready` asks middleware, from `middleware` of the configuration
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    /// Service of the gRPC Health protocol, empty for the whole server
+    pub service: String,
+    pub timeout: Duration
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        HealthCheck {
+            service: String::new(),
+            timeout: Duration::from_secs(2)
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for HealthCheck {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        _depot.inject(self.clone());
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct LiveResponse {
+    pub status: &'static str,
+    pub version: &'static str
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct ReadyResponse {
+    pub status: &'static str,
+    pub middleware: MiddlewareHealth
+}
+
+/// Result of the call to middleware, `status` is the serving status of the
+/// gRPC Health protocol or the gRPC code of the error
+#[derive(Serialize, Debug, ToSchema)]
+pub struct MiddlewareHealth {
+    pub ready: bool,
+    pub status: String,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>
+}
+
+/// Call the gRPC Health protocol of middleware through the shared channel. A middleware
+/// without it is ready when it answers, the channel is connected anyway
+pub async fn check_middleware(channel: Channel, check: &HealthCheck) -> MiddlewareHealth {
+    let started = Instant::now();
+    let mut client = HealthClient::new(channel);
+    let result = tokio::time::timeout(check.timeout, client.check(tonic::Request::new(HealthCheckRequest {
+        service: check.service.clone()
+    }))).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(Ok(response)) => {
+            let status = ServingStatus::from_i32(response.into_inner().status).unwrap_or(ServingStatus::Unknown);
+            MiddlewareHealth {
+                ready: status == ServingStatus::Serving,
+                status: status.as_str_name().to_owned(),
+                latency_ms,
+                message: None
+            }
+        },
+        Ok(Err(error)) if error.code() == Code::Unimplemented => MiddlewareHealth {
+            ready: true,
+            status: code_name(error.code()).to_owned(),
+            latency_ms,
+            message: Some("gRPC Health protocol is not implemented by middleware".to_owned())
+        },
+        Ok(Err(error)) => MiddlewareHealth {
+            ready: false,
+            status: code_name(error.code()).to_owned(),
+            latency_ms,
+            message: Some(error.message().to_owned())
+        },
+        Err(_) => MiddlewareHealth {
+            ready: false,
+            status: code_name(Code::DeadlineExceeded).to_owned(),
+            latency_ms,
+            message: Some(format!("No answer after {:?}", check.timeout))
+        }
+    }
+}
+
+/// The process is up, middleware is not called
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "Health",
+    security(()),
+    responses(
+        (status = 200, description = "Server is running", body = LiveResponse)
+    )
+)]
+#[handler]
+pub async fn live(_res: &mut Response) {
+    _res.render(Json(LiveResponse {
+        status: "UP",
+        version: env!("CARGO_PKG_VERSION")
+    }));
+}
+
+/// Middleware can be reached, otherwise `503 Service Unavailable` so no traffic is routed here
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "Health",
+    security(()),
+    responses(
+        (status = 200, description = "Middleware is ready", body = ReadyResponse),
+        (status = 503, description = "Middleware is not ready", body = ReadyResponse)
+    )
+)]
+#[handler]
+pub async fn ready(_depot: &mut Depot, _res: &mut Response) {
+    let check = _depot.obtain::<HealthCheck>().cloned().unwrap_or_default();
+    let middleware = match middleware_channel(_depot) {
+        Ok(channel) => check_middleware(channel, &check).await,
+        Err(error) => MiddlewareHealth {
+            ready: false,
+            status: code_name(Code::Internal).to_owned(),
+            latency_ms: 0,
+            message: Some(error.message)
+        }
+    };
+    if !middleware.ready {
+        log::warn!("Middleware is not ready: {} {}", middleware.status, middleware.message.clone().unwrap_or_default());
+        _res.status_code(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    _res.render(Json(ReadyResponse {
+        status: if middleware.ready { "UP" } else { "DOWN" },
+        middleware
+    }));
+}
@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use salvo::http::ParseError;
+use salvo::prelude::*;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use tonic::Code;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::transport::Channel;
+
+use crate::auth::{TokenValidation, TokenValidator};
+use crate::errors::BackendError;
+use crate::metrics::{prometheus_metrics, Metrics};
+use crate::models::documents::{ConversionMode, EntityResponse};
+use crate::models::query::MAX_PAGE_SIZE;
+
+pub mod entities;
+pub mod health;
+pub mod openapi;
+pub mod process;
+pub mod shutdown;
+
+use entities::{create_entity, create_table_entity, delete_entities, delete_entities_not_allowed, delete_entity, delete_table_entity, get_entity, list_entities, update_entity, update_table_entity};
+use health::{live, ready, HealthCheck};
+use openapi::{ApiDocConfig, OpenApiJson, SwaggerUi, SwaggerUiAsset};
+use process::run_process;
+
+/// What the router needs to serve the ADempiere endpoints
+pub struct RouterConfig {
+    /// Channel to adempiere-middleware shared by all requests, see `channel::connect_lazy`
+    pub channel: Channel,
+    /// Tokens are verified locally when it is set, otherwise only by middleware
+    pub token_validator: Option<Arc<TokenValidator>>,
+    pub limits: Limits,
+    /// How `health/ready` asks middleware
+    pub health_check: HealthCheck,
+    /// Requests are measured and served in `metrics` when it is set
+    pub metrics: Option<Metrics>,
+    pub api_doc: ApiDocConfig
+}
+
+impl RouterConfig {
+    pub fn new(channel: Channel) -> Self {
+        RouterConfig {
+            channel,
+            token_validator: None,
+            limits: Limits::default(),
+            health_check: HealthCheck::default(),
+            metrics: None,
+            api_doc: ApiDocConfig::default()
+        }
+    }
+
+    pub fn with_token_validator(mut self, token_validator: TokenValidator) -> Self {
+        self.token_validator = Some(Arc::new(token_validator));
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn with_health_check(mut self, health_check: HealthCheck) -> Self {
+        self.health_check = health_check;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    pub fn with_api_doc(mut self, api_doc: ApiDocConfig) -> Self {
+        self.api_doc = api_doc;
+        self
+    }
+}
+
+/// Limits of the requests, from `limits` of the configuration
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_page_size: i32
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_page_size: MAX_PAGE_SIZE
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for Limits {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        _depot.inject(*self);
+    }
+}
+
+/// Router with all endpoints, `v1/entities`, `v1/process`, `health`, `metrics`,
+/// `openapi.json` and `swagger-ui`, it can be served as is or pushed into another router
+pub fn build_router(config: RouterConfig) -> Router {
+    let mut api = Router::new();
+    if let Some(validator) = config.token_validator {
+        api = api.hoop(TokenValidation { validator });
+    }
+    let api = api
+        .push(
+            Router::with_path("v1/entities")
+                .post(create_entity)
+                .patch(update_entity)
+                .delete(delete_entity)
+                .push(
+                    Router::with_path("delete")
+                        .post(delete_entities)
+                        .handle(delete_entities_not_allowed)
+                )
+                .push(
+                    Router::with_path("<table_name>")
+                        .get(list_entities)
+                        .post(create_table_entity)
+                        .push(
+                            Router::with_path("<id>")
+                                .get(get_entity)
+                                .patch(update_table_entity)
+                                .delete(delete_table_entity)
+                        )
+                )
+        )
+        .push(
+            Router::with_path("v1/process")
+                .post(run_process)
+        );
+    let mut swagger_ui = Router::with_path("swagger-ui")
+        .get(SwaggerUi {
+            local_assets: config.api_doc.swagger_ui_assets.is_some()
+        });
+    if let Some(directory) = config.api_doc.swagger_ui_assets {
+        swagger_ui = swagger_ui.push(
+            Router::with_path("<file>")
+                .get(SwaggerUiAsset { directory })
+        );
+    }
+    let mut router = Router::new();
+    if let Some(metrics) = config.metrics {
+        router = router
+            .hoop(metrics)
+            .push(
+                Router::with_path("metrics")
+                    .get(prometheus_metrics)
+            );
+    }
+    router
+        .hoop(MiddlewareChannel { channel: config.channel })
+        .hoop(config.limits)
+        .push(api)
+        .push(
+            Router::with_path("health")
+                .push(
+                    Router::with_path("live")
+                        .get(live)
+                )
+                .push(
+                    Router::with_path("ready")
+                        .hoop(config.health_check)
+                        .get(ready)
+                )
+        )
+        .push(
+            Router::with_path("openapi.json")
+                .get(OpenApiJson {
+                    server_url: config.api_doc.server_url
+                })
+        )
+        .push(swagger_ui)
+}
+
+/// Share a single gRPC channel with all handlers through the depot
+pub struct MiddlewareChannel {
+    pub channel: Channel
+}
+
+#[async_trait]
+impl Handler for MiddlewareChannel {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        _depot.inject(self.channel.clone());
+    }
+}
+
+/// Token of the incoming request, forwarded as is to middleware
+pub(crate) fn authorization_token(_req: &salvo::Request) -> Result<MetadataValue<Ascii>, BackendError> {
+    let token_value = _req.header::<String>("authorization");
+    if token_value.is_none() {
+        return Err(BackendError::unauthenticated("Token is Mandatory"));
+    }
+    match token_value.unwrap().parse() {
+        Ok(token) => Ok(token),
+        Err(_) => Err(BackendError::invalid_argument("Invalid Token").with_details(json!({"header": "authorization"})))
+    }
+}
+
+/// JSON body of the request, a body that can not be read is rejected with the error
+/// envelope instead of the plain error of the salvo extractor
+pub(crate) async fn parse_document<T: DeserializeOwned>(_req: &mut salvo::Request) -> Result<T, BackendError> {
+    match _req.parse_json::<T>().await {
+        Ok(document) => Ok(document),
+        Err(ParseError::SerdeJson(error)) => Err(BackendError::invalid_argument("Invalid Body").with_details(json!({"error": error.to_string()}))),
+        Err(ParseError::InvalidContentType) => Err(BackendError::invalid_argument("Body must be JSON").with_details(json!({"header": "content-type"}))),
+        Err(ParseError::Other(error)) => Err(BackendError::new(StatusCode::PAYLOAD_TOO_LARGE, Code::ResourceExhausted, "Body is too large").with_details(json!({"error": error.to_string()}))),
+        Err(error) => Err(BackendError::invalid_argument("Invalid Body").with_details(json!({"error": error.to_string()})))
+    }
+}
+
+/// Values are checked strictly unless the request asks for `?lenient=true`
+pub(crate) fn conversion_mode(_req: &salvo::Request) -> ConversionMode {
+    if _req.query::<bool>("lenient").unwrap_or_default() {
+        return ConversionMode::Lenient;
+    }
+    ConversionMode::Strict
+}
+
+pub(crate) fn middleware_channel(_depot: &Depot) -> Result<Channel, BackendError> {
+    match _depot.obtain::<Channel>() {
+        Some(channel) => Ok(channel.clone()),
+        None => Err(BackendError::internal("Middleware Channel is not available"))
+    }
+}
+
+/// Limits of the router, the default ones when the router is used without them
+pub(crate) fn limits(_depot: &Depot) -> Limits {
+    _depot.obtain::<Limits>().copied().unwrap_or_default()
+}
+
+/// Attributes are rendered as a plain JSON object with `?format=flat`
+pub(crate) fn is_flat_format(_req: &salvo::Request) -> bool {
+    _req.query::<String>("format").map(|format| format.eq_ignore_ascii_case("flat")).unwrap_or_default()
+}
+
+pub(crate) fn render_entity(_req: &salvo::Request, _res: &mut Response, entity: EntityResponse) {
+    if is_flat_format(_req) {
+        return _res.render(Json(entity.into_flat()));
+    }
+    _res.render(Json(entity));
+}
+
+pub(crate) fn table_name_param(_req: &salvo::Request) -> Result<String, BackendError> {
+    match _req.param::<String>("table_name") {
+        Some(table_name) => Ok(table_name),
+        None => Err(BackendError::invalid_argument("Table Is Mandatory").with_details(json!({"field": "table_name"})))
+    }
+}
+
+pub(crate) fn id_param(_req: &salvo::Request) -> Result<i32, BackendError> {
+    match _req.param::<i32>("id") {
+        Some(id) => Ok(id),
+        None => Err(BackendError::invalid_argument("Invalid ID").with_details(json!({"field": "id"})))
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::{Path, PathBuf};
+
+use salvo::fs::NamedFile;
+use salvo::prelude::*;
+use utoipa::{Modify, OpenApi};
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::server::Server;
+
+use crate::errors::ErrorResponse;
+use crate::models::documents::{EntityNewDocument, EntityNew, EntityResponse, EntitiesResponse, EntityUpdateDocument, EntityUpdate, EntityDeleteDocument, EntityDelete, EntitiesDeleteDocument, EntitiesDelete, EntitiesDeleteResponse, EntityDeleteResult, EntityAttributesDocument, RunProcessDocument, RunProcess, ProcessResponse, ProcessLog, Attributes, KeyAndValue, KeyAndValueSelection};
+use crate::server::health::{LiveResponse, MiddlewareHealth, ReadyResponse};
+use crate::server::{entities, health, process};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "ADempiere Backend", description = "Rest API for ADempiere, published over adempiere-middleware"),
+    paths(
+        entities::create_entity,
+        entities::update_entity,
+        entities::delete_entity,
+        entities::delete_entities,
+        entities::list_entities,
+        entities::create_table_entity,
+        entities::get_entity,
+        entities::update_table_entity,
+        entities::delete_table_entity,
+        process::run_process,
+        health::live,
+        health::ready
+    ),
+    components(
+        schemas(
+            EntityNewDocument,
+            EntityNew,
+            EntityUpdateDocument,
+            EntityUpdate,
+            EntityDeleteDocument,
+            EntityDelete,
+            EntitiesDeleteDocument,
+            EntitiesDelete,
+            EntityAttributesDocument,
+            Attributes,
+            KeyAndValue,
+            KeyAndValueSelection,
+            RunProcessDocument,
+            RunProcess,
+            EntityResponse,
+            EntitiesResponse,
+            EntitiesDeleteResponse,
+            EntityDeleteResult,
+            ProcessResponse,
+            ProcessLog,
+            ErrorResponse,
+            LiveResponse,
+            ReadyResponse,
+            MiddlewareHealth
+        )
+    ),
+    modifiers(&TokenSecurity),
+    security(
+        ("token" = [])
+    ),
+    tags(
+        (name = "Entities", description = "Create, read, update and delete records of any table"),
+        (name = "Processes", description = "Run processes and reports"),
+        (name = "Health", description = "Liveness and readiness probes, without token")
+    )
+)]
+pub struct ApiDoc;
+
+/// Token sent as `Authorization: Bearer <Token>` and forwarded to middleware
+struct TokenSecurity;
+
+impl Modify for TokenSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme("token", SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()));
+        }
+    }
+}
+
+/// Version of swagger-ui-dist loaded from unpkg when the files are not served by the router
+pub const SWAGGER_UI_VERSION: &str = "5.11.0";
+const SWAGGER_UI_CDN: &str = "https://unpkg.com/swagger-ui-dist@";
+/// Files of swagger-ui-dist used by the page, the only ones served from `swagger_ui_assets`
+const SWAGGER_UI_FILES: [&str; 2] = ["swagger-ui.css", "swagger-ui-bundle.js"];
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8" />
+    <title>ADempiere Backend API</title>
+    <link rel="stylesheet" href="{assets}/swagger-ui.css" crossorigin="anonymous" referrerpolicy="no-referrer" />
+</head>
+<body>
+    <div id="swagger-ui"></div>
+    <script src="{assets}/swagger-ui-bundle.js" crossorigin="anonymous" referrerpolicy="no-referrer"></script>
+    <script>
+        window.onload = () => {
+            window.ui = SwaggerUIBundle({
+                url: "openapi.json",
+                dom_id: "#swagger-ui",
+                persistAuthorization: true
+            });
+        };
+    </script>
+</body>
+</html>
+"##;
+
+/// How `openapi.json` and `swagger-ui` are served
+#[derive(Debug, Clone, Default)]
+pub struct ApiDocConfig {
+    /// URL of the endpoints in the document, e.g. `https://erp.example.com/api`. When it is not set
+    /// the path where the router is mounted is used, `/erp` for `/erp/openapi.json`
+    pub server_url: Option<String>,
+    /// Directory with `swagger-ui.css` and `swagger-ui-bundle.js` of swagger-ui-dist, served in
+    /// `swagger-ui/` instead of loading them from unpkg
+    pub swagger_ui_assets: Option<PathBuf>
+}
+
+/// The OpenAPI document with the URL of the server
+pub struct OpenApiJson {
+    pub server_url: Option<String>
+}
+
+#[async_trait]
+impl Handler for OpenApiJson {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let server_url = match &self.server_url {
+            Some(server_url) => server_url.to_owned(),
+            None => _req.uri().path().strip_suffix("/openapi.json").unwrap_or_default().to_owned()
+        };
+        let mut document = ApiDoc::openapi();
+        if !server_url.is_empty() {
+            document.servers = Some(vec![Server::new(server_url)]);
+        }
+        _res.render(Json(document));
+    }
+}
+
+/// Page of Swagger UI, with the files served by the router or pinned from unpkg
+pub struct SwaggerUi {
+    pub local_assets: bool
+}
+
+#[async_trait]
+impl Handler for SwaggerUi {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let assets = if self.local_assets {
+            "swagger-ui".to_owned()
+        } else {
+            format!("{}{}", SWAGGER_UI_CDN, SWAGGER_UI_VERSION)
+        };
+        _res.render(Text::Html(SWAGGER_UI.replace("{assets}", &assets)));
+    }
+}
+
+/// A file of swagger-ui-dist from `swagger_ui_assets`
+pub struct SwaggerUiAsset {
+    pub directory: PathBuf
+}
+
+#[async_trait]
+impl Handler for SwaggerUiAsset {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        match _req.param::<String>("file").filter(|file| SWAGGER_UI_FILES.contains(&file.as_str())) {
+            Some(file) => NamedFile::send_file(self.directory.join(file), _req.headers(), _res).await,
+            None => {
+                _res.status_code(StatusCode::NOT_FOUND);
+            }
+        }
+    }
+}
+
+/// `swagger_ui_assets` has the files used by the page
+pub fn check_swagger_ui_assets(directory: &Path) -> Result<(), String> {
+    match SWAGGER_UI_FILES.iter().find(|file| !directory.join(file).is_file()) {
+        Some(file) => Err(format!("{} not found in {}", file, directory.display())),
+        None => Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use salvo::prelude::*;
+use serde_json::json;
+
+use crate::channel::middleware_client;
+use crate::errors::BackendError;
+use crate::metrics::{label_process_code, label_table_name, observe_call};
+use crate::middleware::{KeyValue, KeyValueSelection, RunBusinessProcessRequest};
+use crate::models::documents::{RunProcessDocument, ProcessResponse};
+use crate::server::{authorization_token, conversion_mode, middleware_channel, parse_document};
+
+#[utoipa::path(
+    post,
+    path = "/v1/process",
+    tag = "Processes",
+    request_body = RunProcessDocument,
+    params(
+        ("lenient" = Option<bool>, Query, description = "Use the first value found instead of validating value_type")
+    ),
+    responses(
+        (status = 200, description = "Process executed", body = ProcessResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Token is Mandatory", body = ErrorResponse),
+        (status = 503, description = "Middleware is unavailable", body = ErrorResponse)
+    )
+)]
+#[handler]
+pub async fn run_process<'a>(_req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response) -> Result<(), BackendError> {
+    let _document: RunProcessDocument = parse_document(_req).await?;
+    let _process = _document.process;
+    if _process.is_none() {
+        return Err(BackendError::invalid_argument("Process Is Mandatory").with_details(json!({"field": "process"})));
+    }
+    let _process = _process.unwrap();
+    if _process.process_code.is_none() {
+        return Err(BackendError::invalid_argument("Process Code is Mandatory").with_details(json!({"field": "process_code"})));
+    }
+    let selections = _process.selections.unwrap_or_default();
+    if selections.iter().any(|selection| selection.selection_id.is_none()) {
+        return Err(BackendError::invalid_argument("Selection ID is Mandatory").with_details(json!({"field": "selections.selection_id"})));
+    }
+    let token = authorization_token(_req)?;
+    let mode = conversion_mode(_req);
+    let parameters: Vec<KeyValue> = _process.parameters.unwrap_or_default().iter().map(|value| value.to_owned().to_grpc_value_with_mode(mode)).collect::<Result<_, _>>()?;
+    let selections: Vec<KeyValueSelection> = selections.into_iter().map(|selection| selection.to_grpc_value_with_mode(mode)).collect::<Result<_, _>>()?;
+    label_process_code(_depot, _process.process_code.as_ref().unwrap());
+    if let Some(table_name) = &_process.table_name {
+        label_table_name(_depot, table_name);
+    }
+    let mut client = middleware_client(middleware_channel(_depot)?, token);
+    match observe_call(_depot, "RunBusinessProcess", client.run_business_process(tonic::Request::new(RunBusinessProcessRequest {
+        process_code: _process.process_code.unwrap(),
+        table_name: _process.table_name.unwrap_or_default(),
+        id: _process.id.unwrap_or_default(),
+        table_selected_id: _process.table_selected_id.unwrap_or_default(),
+        parameters,
+        selections
+    }))).await {
+        Ok(response) => {
+            let process_respose = response.get_ref();
+            log::info!("{:?}", process_respose.to_owned());
+            _res.status_code(StatusCode::OK);
+            _res.render(Json(ProcessResponse::from_process_response(process_respose.to_owned())));
+            Ok(())
+        }, 
+        Err(error) => Err(error.into())
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use salvo::http::header::{HeaderValue, CONNECTION};
+use salvo::prelude::*;
+use tokio::sync::Notify;
+
+/// Request that is being handled
+#[derive(Debug, Clone)]
+pub struct InFlightRequest {
+    pub method: String,
+    pub path: String,
+    pub started: Instant
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    next_id: AtomicU64,
+    closing: AtomicBool,
+    requests: Mutex<HashMap<u64, InFlightRequest>>,
+    idle: Notify
+}
+
+/// Requests in flight, used as hoop to know what must be drained on shutdown
+#[derive(Debug, Clone, Default)]
+pub struct InFlightRequests {
+    registry: Arc<Registry>
+}
+
+/// Remove the request when it is done, also when its connection is dropped
+struct Tracked {
+    registry: Arc<Registry>,
+    id: u64
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        let mut requests = self.registry.requests.lock().unwrap();
+        requests.remove(&self.id);
+        if requests.is_empty() {
+            self.registry.idle.notify_waiters();
+        }
+    }
+}
+
+impl InFlightRequests {
+    pub fn new() -> Self {
+        InFlightRequests::default()
+    }
+
+    pub fn count(&self) -> usize {
+        self.registry.requests.lock().unwrap().len()
+    }
+
+    /// Responses are sent with `Connection: close` from now on, so clients
+    /// do not keep connections open while the server is stopping
+    pub fn close(&self) {
+        self.registry.closing.store(true, Ordering::SeqCst);
+    }
+
+    /// Wait until every request is done or the timeout is reached, the requests
+    /// still in flight are returned (they are aborted when the server stops)
+    pub async fn drain(&self, timeout: Duration) -> Vec<InFlightRequest> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let idle = self.registry.idle.notified();
+            let pending: Vec<InFlightRequest> = self.registry.requests.lock().unwrap().values().cloned().collect();
+            if pending.is_empty() {
+                return pending;
+            }
+            if tokio::time::timeout_at(deadline, idle).await.is_err() {
+                return self.registry.requests.lock().unwrap().values().cloned().collect();
+            }
+        }
+    }
+
+    fn track(&self, _req: &salvo::Request) -> Tracked {
+        let id = self.registry.next_id.fetch_add(1, Ordering::SeqCst);
+        self.registry.requests.lock().unwrap().insert(id, InFlightRequest {
+            method: _req.method().to_string(),
+            path: _req.uri().path().to_owned(),
+            started: Instant::now()
+        });
+        Tracked {
+            registry: self.registry.clone(),
+            id
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for InFlightRequests {
+    async fn handle(&self, _req: &mut salvo::Request, _depot: &mut Depot, _res: &mut Response, _ctrl: &mut FlowCtrl) {
+        let _tracked = self.track(_req);
+        _ctrl.call_next(_req, _depot, _res).await;
+        if self.registry.closing.load(Ordering::SeqCst) {
+            _res.headers_mut().insert(CONNECTION, HeaderValue::from_static("close"));
+        }
+    }
+}
+
+/// Completes on SIGTERM (`docker stop`) or SIGINT (Ctrl+C)
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(error) = tokio::signal::ctrl_c().await {
+            log::error!("SIGINT handler can not be installed: {}", error);
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            },
+            Err(error) => {
+                log::error!("SIGTERM handler can not be installed: {}", error);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = interrupt => log::info!("SIGINT received"),
+        _ = terminate => log::info!("SIGTERM received")
+    }
+}
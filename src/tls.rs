@@ -0,0 +1,175 @@
+use std::fs;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use rustls_pemfile::Item;
+use salvo::conn::{http1, http2};
+use salvo::http::uri::Scheme;
+use salvo::runtimes::TokioExecutor;
+use salvo::Service;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
+use tokio_rustls::rustls::{self, PrivateKey};
+use tokio_rustls::TlsAcceptor;
+use tonic::transport::{Certificate, ClientTlsConfig, Identity};
+
+use crate::config::{MiddlewareTlsConfig, ServerTlsConfig};
+
+fn read_file(file: &str) -> Result<Vec<u8>, String> {
+    fs::read(file).map_err(|error| format!("{} can not be read: {}", file, error))
+}
+
+/// PEM certificates of the file, at least one is required
+pub fn read_certificates(file: &str) -> Result<Vec<u8>, String> {
+    let content = read_file(file)?;
+    match rustls_pemfile::certs(&mut content.as_slice()) {
+        Ok(certificates) if !certificates.is_empty() => Ok(content),
+        _ => Err(format!("{} has no PEM certificate", file))
+    }
+}
+
+/// PEM private key of the file, PKCS#8 or RSA as supported by the listener and the channel
+pub fn read_private_key(file: &str) -> Result<Vec<u8>, String> {
+    let content = read_file(file)?;
+    let items = rustls_pemfile::read_all(&mut content.as_slice()).map_err(|_| format!("{} is not a valid PEM file", file))?;
+    if items.iter().any(|item| matches!(item, Item::PKCS8Key(_) | Item::RSAKey(_))) {
+        return Ok(content);
+    }
+    Err(format!("{} has no PKCS#8 or RSA private key", file))
+}
+
+/// Certificate and key of the HTTPS listener, HTTP/2 and HTTP/1.1 are offered by ALPN
+pub fn rustls_server_config(cert_file: &str, key_file: &str) -> Result<Arc<rustls::ServerConfig>, String> {
+    let certificates = rustls_pemfile::certs(&mut read_certificates(cert_file)?.as_slice())
+        .map_err(|_| format!("{} has no PEM certificate", cert_file))?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+    let key = rustls_pemfile::read_all(&mut read_private_key(key_file)?.as_slice())
+        .map_err(|_| format!("{} is not a valid PEM file", key_file))?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(key) | Item::RSAKey(key) => Some(PrivateKey(key)),
+            _ => None
+        })
+        .ok_or_else(|| format!("{} has no PKCS#8 or RSA private key", key_file))?;
+    let mut config = rustls::ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .map_err(|error| format!("Invalid certificate {} or key {}: {}", cert_file, key_file, error))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+fn modified(file: &str) -> Option<SystemTime> {
+    fs::metadata(file).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Serve the router with HTTPS until `stop` completes, then the listener is closed and the
+/// open connections are shut down gracefully: this returns when every response was written.
+/// The certificate is loaded again when the files change (checked each `reload_interval`),
+/// an invalid change is logged and the certificate in use is kept.
+/// The protocol is chosen by ALPN: the rustls listener of salvo only serves HTTP/2
+pub async fn serve_tls(listener: TcpListener, service: Service, tls: &ServerTlsConfig, stop: impl Future<Output = ()>) -> Result<(), String> {
+    let cert_file = tls.cert_file.clone().ok_or_else(|| "TLS Certificate is Mandatory".to_owned())?;
+    let key_file = tls.key_file.clone().ok_or_else(|| "TLS Key is Mandatory".to_owned())?;
+    let local_address = listener.local_addr().map_err(|error| error.to_string())?;
+    let mut loaded = (modified(&cert_file), modified(&key_file));
+    let mut acceptor = TlsAcceptor::from(rustls_server_config(&cert_file, &key_file)?);
+    let reload_interval = Duration::from_secs(tls.reload_interval);
+    let mut reload = tokio::time::interval_at(tokio::time::Instant::now() + reload_interval, reload_interval);
+    reload.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let service = Arc::new(service);
+    let (closing, closed) = watch::channel(false);
+    let mut connections = JoinSet::new();
+    tokio::pin!(stop);
+    loop {
+        tokio::select! {
+            _ = &mut stop => break,
+            Some(_) = connections.join_next(), if !connections.is_empty() => {},
+            _ = reload.tick() => {
+                let current = (modified(&cert_file), modified(&key_file));
+                if current == loaded {
+                    continue;
+                }
+                loaded = current;
+                match rustls_server_config(&cert_file, &key_file) {
+                    Ok(config) => {
+                        acceptor = TlsAcceptor::from(config);
+                        log::info!("TLS Certificate reloaded from {}", cert_file);
+                    },
+                    Err(error) => log::error!("TLS Certificate not reloaded: {}", error)
+                }
+            },
+            accepted = listener.accept() => {
+                let (stream, remote_address) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(error) => {
+                        log::error!("Connection not accepted: {}", error);
+                        continue;
+                    }
+                };
+                let acceptor = acceptor.clone();
+                let handler = service.hyper_handler(local_address.into(), remote_address.into(), Scheme::HTTPS, None);
+                let mut closed = closed.clone();
+                connections.spawn(async move {
+                    let stream = match acceptor.accept(stream).await {
+                        Ok(stream) => stream,
+                        Err(error) => {
+                            log::debug!("TLS Handshake failed with {}: {}", remote_address, error);
+                            return;
+                        }
+                    };
+                    //  On stop the request in progress is answered and then the connection is closed
+                    let result = if stream.get_ref().1.alpn_protocol() == Some(b"h2") {
+                        let connection = http2::Builder::new(TokioExecutor).serve_connection(stream, handler);
+                        tokio::pin!(connection);
+                        tokio::select! {
+                            result = connection.as_mut() => result,
+                            _ = closed.changed() => {
+                                connection.as_mut().graceful_shutdown();
+                                connection.await
+                            }
+                        }
+                    } else {
+                        let connection = http1::Builder::new().serve_connection(stream, handler).with_upgrades();
+                        tokio::pin!(connection);
+                        tokio::select! {
+                            result = connection.as_mut() => result,
+                            _ = closed.changed() => {
+                                connection.as_mut().graceful_shutdown();
+                                connection.await
+                            }
+                        }
+                    };
+                    if let Err(error) = result {
+                        log::debug!("Connection with {} failed: {}", remote_address, error);
+                    }
+                });
+            }
+        }
+    }
+    drop(listener);
+    let _ = closing.send(true);
+    while connections.join_next().await.is_some() {}
+    Ok(())
+}
+
+/// TLS of the channel to middleware, with the client certificate for mutual TLS when it is set
+pub fn client_tls_config(tls: &MiddlewareTlsConfig) -> Result<ClientTlsConfig, String> {
+    let mut config = ClientTlsConfig::new();
+    if let Some(ca_file) = &tls.ca_file {
+        config = config.ca_certificate(Certificate::from_pem(read_certificates(ca_file)?));
+    }
+    if let (Some(cert_file), Some(key_file)) = (&tls.cert_file, &tls.key_file) {
+        config = config.identity(Identity::from_pem(read_certificates(cert_file)?, read_private_key(key_file)?));
+    }
+    if let Some(domain_name) = &tls.domain_name {
+        config = config.domain_name(domain_name);
+    }
+    Ok(config)
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use adempiere_backend_rs::auth::{verified_claims, TokenValidation, TokenValidator};
+use adempiere_backend_rs::config::AuthConfig;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use salvo::prelude::*;
+use salvo::test::{ResponseExt, TestClient};
+use serde_json::{json, Value};
+
+const CERTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/certs");
+const SECRET: &[u8] = b"secret shared with middleware";
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("time after epoch").as_secs()
+}
+
+fn hs256(claims: Value) -> String {
+    encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(SECRET)).expect("signed token")
+}
+
+fn signed_with_file(algorithm: Algorithm, key_id: &str, claims: Value) -> String {
+    let pem = std::fs::read(format!("{}/{}", CERTS, if algorithm == Algorithm::RS256 { "jwt-rsa.key" } else { "jwt-ec.key" })).expect("key file");
+    let key = match algorithm {
+        Algorithm::RS256 => EncodingKey::from_rsa_pem(&pem),
+        _ => EncodingKey::from_ec_pem(&pem)
+    }.expect("valid key");
+    let mut header = Header::new(algorithm);
+    header.kid = Some(key_id.to_owned());
+    encode(&header, &claims, &key).expect("signed token")
+}
+
+fn secret_validator(audience: &[&str]) -> TokenValidator {
+    TokenValidator::new(Some(SECRET), None, audience.iter().map(|audience| audience.to_string()).collect()).expect("valid validator")
+}
+
+fn jwks_validator() -> TokenValidator {
+    TokenValidator::from_config(&AuthConfig {
+        jwks_file: Some(format!("{}/jwks.json", CERTS)),
+        ..Default::default()
+    }).expect("valid JWKS file").expect("validation enabled")
+}
+
+fn reason(validator: &TokenValidator, token: &str) -> Value {
+    let error = validator.validate(token).expect_err("invalid token");
+    assert_eq!(error.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(error.code, "UNAUTHENTICATED");
+    error.details.expect("details")["reason"].clone()
+}
+
+#[test]
+fn valid_hs256_token() {
+    let token = hs256(json!({"sub": "11", "aud": "102", "exp": now() + 60, "AD_Client_ID": 1000000}));
+    let claims = secret_validator(&["102"]).validate(&format!("Bearer {}", token)).expect("valid token");
+    assert_eq!(claims.sub.as_deref(), Some("11"));
+    assert_eq!(claims.extra["AD_Client_ID"], 1000000);
+    assert!(secret_validator(&[]).validate(&token).is_ok());
+}
+
+#[test]
+fn expired_token_is_rejected() {
+    let token = hs256(json!({"sub": "11", "exp": now() - 600}));
+    assert_eq!(reason(&secret_validator(&[]), &token), "token is expired");
+}
+
+#[test]
+fn token_not_valid_yet_is_rejected() {
+    let token = hs256(json!({"sub": "11", "nbf": now() + 600}));
+    assert_eq!(reason(&secret_validator(&[]), &token), "token is not valid yet");
+}
+
+#[test]
+fn wrong_audience_is_rejected() {
+    let token = hs256(json!({"sub": "11", "aud": "999"}));
+    assert_eq!(reason(&secret_validator(&["102"]), &token), "invalid audience");
+}
+
+#[test]
+fn wrong_secret_is_rejected() {
+    let token = encode(&Header::new(Algorithm::HS256), &json!({"sub": "11"}), &EncodingKey::from_secret(b"other")).expect("signed token");
+    assert_eq!(reason(&secret_validator(&[]), &token), "invalid signature");
+    assert_eq!(reason(&secret_validator(&[]), "not a token"), "malformed token");
+}
+
+#[test]
+fn rs256_and_es256_tokens_are_verified_with_the_jwks() {
+    let validator = jwks_validator();
+    let token = signed_with_file(Algorithm::RS256, "rsa-test", json!({"sub": "rsa", "exp": now() + 60}));
+    assert_eq!(validator.validate(&token).expect("valid RS256 token").sub.as_deref(), Some("rsa"));
+    let token = signed_with_file(Algorithm::ES256, "ec-test", json!({"sub": "ec", "exp": now() + 60}));
+    assert_eq!(validator.validate(&token).expect("valid ES256 token").sub.as_deref(), Some("ec"));
+    let token = signed_with_file(Algorithm::RS256, "unknown", json!({"sub": "rsa"}));
+    assert_eq!(reason(&validator, &token), "no key to verify the signature");
+}
+
+#[test]
+fn hs256_token_is_rejected_without_secret() {
+    let token = hs256(json!({"sub": "11"}));
+    assert_eq!(reason(&jwks_validator(), &token), "no key to verify the signature");
+}
+
+#[handler]
+async fn claims_subject(_depot: &mut Depot, _res: &mut Response) {
+    let subject = verified_claims(_depot).and_then(|claims| claims.sub.clone()).unwrap_or_default();
+    _res.render(Json(json!({"sub": subject})));
+}
+
+#[tokio::test]
+async fn claims_are_injected_in_the_depot() {
+    let router = Router::new().hoop(TokenValidation {
+        validator: Arc::new(secret_validator(&[]))
+    }).get(claims_subject);
+    let service = Service::new(router);
+    let mut response = TestClient::get("http://127.0.0.1:7878/")
+        .add_header("authorization", format!("Bearer {}", hs256(json!({"sub": "11"}))), true)
+        .send(&service)
+        .await;
+    assert_eq!(response.status_code, Some(StatusCode::OK));
+    let body: Value = response.take_json().await.expect("JSON body");
+    assert_eq!(body["sub"], "11");
+    let mut response = TestClient::get("http://127.0.0.1:7878/").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::UNAUTHORIZED));
+    let body: Value = response.take_json().await.expect("JSON body");
+    assert_eq!(body["code"], "UNAUTHENTICATED");
+}
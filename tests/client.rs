@@ -0,0 +1,98 @@
+mod common;
+
+use std::process::{Command, Output};
+use std::time::Duration;
+
+use adempiere_backend_rs::mock::{MockMiddleware, FIRST_ID};
+use common::{TestServer, TOKEN};
+
+/// Run the `client` binary, its report is written to the standard output
+async fn client(arguments: &[&str]) -> Output {
+    let arguments: Vec<String> = arguments.iter().map(|argument| argument.to_string()).collect();
+    tokio::task::spawn_blocking(move || Command::new(env!("CARGO_BIN_EXE_client"))
+        .current_dir(std::env::temp_dir())
+        .env_remove("TOKEN")
+        .args(["--token", TOKEN])
+        .args(arguments)
+        .output()
+        .expect("client finished")).await.expect("client executed")
+}
+
+async fn grpc_client(mock: &MockMiddleware, arguments: &[&str]) -> String {
+    let address = mock.clone().spawn().await.expect("mock middleware started");
+    let url = format!("http://{}", address);
+    let output = client(&[&["--target", "grpc", "--url", &url], arguments].concat()).await;
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Value in milliseconds of a latency of the report, e.g. `p95`
+fn latency(report: &str, name: &str) -> f64 {
+    report.lines()
+        .find_map(|line| line.trim().strip_prefix("Latency:"))
+        .and_then(|line| line.split(',').find_map(|part| part.trim().strip_prefix(name)))
+        .and_then(|value| value.trim().strip_suffix("ms"))
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| panic!("{} in report: {}", name, report))
+}
+
+#[tokio::test]
+async fn template_placeholders_are_replaced() {
+    let file = std::env::temp_dir().join(format!("client-template-{}.json", std::process::id()));
+    std::fs::write(&file, r#"{"Value": "{uuid}", "Name": "Record {index} of {index}", "IsDefault": false}"#).expect("template written");
+    let mock = MockMiddleware::new();
+    let report = grpc_client(&mock, &["--table", "M_Product_Class", "--template", file.to_str().unwrap(), "--count", "3", "--concurrency", "1"]).await;
+    std::fs::remove_file(&file).ok();
+    assert!(report.contains("Requests:   3 (3 succeeded, 0 failed)"), "{}", report);
+    let mut names = Vec::new();
+    let mut values = Vec::new();
+    for id in FIRST_ID..FIRST_ID + 3 {
+        let record = mock.record("M_Product_Class", id).expect("record created");
+        names.push(record["Name"].string_value.clone());
+        values.push(record["Value"].string_value.clone());
+    }
+    names.sort();
+    assert_eq!(names, ["Record 1 of 1", "Record 2 of 2", "Record 3 of 3"]);
+    assert!(values.iter().all(|value| uuid::Uuid::parse_str(value).is_ok()), "{:?}", values);
+    values.sort();
+    values.dedup();
+    assert_eq!(values.len(), 3);
+}
+
+#[tokio::test]
+async fn latency_percentiles_are_reported() {
+    let mock = MockMiddleware::new().with_latency(Duration::from_millis(50));
+    let report = grpc_client(&mock, &["--count", "20", "--concurrency", "4"]).await;
+    assert!(report.contains("Requests:   20 (20 succeeded, 0 failed)"), "{}", report);
+    let (p50, p95, p99, max) = (latency(&report, "p50"), latency(&report, "p95"), latency(&report, "p99"), latency(&report, "max"));
+    assert!(p50 >= 50.0, "{}", report);
+    assert!(p50 <= p95 && p95 <= p99 && p99 <= max, "{}", report);
+}
+
+#[tokio::test]
+async fn errors_are_reported_by_code() {
+    let mock = MockMiddleware::new().with_mandatory("M_Product_Class", &["Help"]);
+    let report = grpc_client(&mock, &["--count", "4", "--concurrency", "2"]).await;
+    assert!(report.contains("Requests:   4 (0 succeeded, 4 failed)"), "{}", report);
+    assert!(report.contains("Errors INVALID_ARGUMENT: 4"), "{}", report);
+    assert_eq!(mock.record_count("M_Product_Class"), 0);
+}
+
+#[tokio::test]
+async fn rest_errors_are_reported_by_status() {
+    let server = TestServer::start(MockMiddleware::new().with_mandatory("M_Product_Class", &["Help"])).await;
+    let url = format!("http://{}", server.address());
+    let output = client(&["--target", "rest", "--url", &url, "--count", "3", "--concurrency", "1"]).await;
+    let report = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(report.contains("Requests:   3 (0 succeeded, 3 failed)"), "{}", report);
+    assert!(report.contains("Errors 400: 3"), "{}", report);
+}
+
+#[tokio::test]
+async fn rest_target_rejects_https() {
+    let output = client(&["--target", "rest", "--url", "https://127.0.0.1:7878", "--count", "1"]).await;
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only http:// is supported with the REST target"));
+}
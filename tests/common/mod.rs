@@ -0,0 +1,142 @@
+//  Each test crate uses a part of the helpers
+#![allow(dead_code)]
+
+use std::io::Read;
+use std::net::TcpListener;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::Duration;
+
+use adempiere_backend_rs::mock::MockMiddleware;
+use hyper::{Body, Client, Method, StatusCode};
+use serde_json::Value;
+
+pub const TOKEN: &str = "Bearer test";
+
+/// REST server (the `server` binary) running against a mock middleware
+pub struct TestServer {
+    pub mock: MockMiddleware,
+    address: String,
+    process: Child
+}
+
+fn free_address() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("free local port");
+    listener.local_addr().expect("local address").to_string()
+}
+
+impl TestServer {
+    pub async fn start(mock: MockMiddleware) -> Self {
+        let middleware_address = mock.clone().spawn().await.expect("mock middleware started");
+        TestServer::start_with_middleware_host(mock, format!("http://{}", middleware_address), &[], false).await
+    }
+
+    /// Server with more variables, its log can be read with `stop`
+    pub async fn start_with(mock: MockMiddleware, variables: &[(&str, &str)]) -> Self {
+        let middleware_address = mock.clone().spawn().await.expect("mock middleware started");
+        TestServer::start_with_middleware_host(mock, format!("http://{}", middleware_address), variables, true).await
+    }
+
+    /// Server with a middleware host where nothing is listening
+    pub async fn start_without_middleware() -> Self {
+        TestServer::start_with_middleware_host(MockMiddleware::new(), format!("http://{}", free_address()), &[], false).await
+    }
+
+    /// Server against any middleware, e.g. one with TLS
+    pub async fn start_with_middleware_host(mock: MockMiddleware, middleware_host: String, variables: &[(&str, &str)], capture_log: bool) -> Self {
+        let address = free_address();
+        let process = Command::new(env!("CARGO_BIN_EXE_server"))
+            .env("HOST", &address)
+            .env("MIDDLEWARE_HOST", middleware_host)
+            .env_remove("CONFIG_FILE")
+            .env_remove("SERVER_PRIVATE_KEY")
+            .env_remove("JWKS_FILE")
+            .envs(variables.iter().copied())
+            .stdout(if capture_log { Stdio::piped() } else { Stdio::null() })
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("server started");
+        for _ in 0..100 {
+            if std::net::TcpStream::connect(&address).is_ok() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        TestServer {
+            mock,
+            address,
+            process
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Send a request and read the status and the JSON body (`Null` when empty)
+    pub async fn request(&self, method: Method, path: &str, token: Option<&str>, body: Option<Value>) -> (StatusCode, Value) {
+        self.try_request(method, path, token, body).await.expect("response from server")
+    }
+
+    /// Same as `request`, the error is returned when the connection is closed without response
+    pub async fn try_request(&self, method: Method, path: &str, token: Option<&str>, body: Option<Value>) -> Result<(StatusCode, Value), hyper::Error> {
+        let mut request = hyper::Request::builder()
+            .method(method)
+            .uri(format!("http://{}{}", self.address, path));
+        if let Some(token) = token {
+            request = request.header("authorization", token);
+        }
+        let request = match body {
+            Some(body) => request.header("content-type", "application/json").body(Body::from(body.to_string())),
+            None => request.body(Body::empty())
+        }.expect("valid request");
+        let response = Client::new().request(request).await?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+    }
+
+    /// Send a body as is, e.g. an invalid JSON
+    pub async fn request_raw(&self, method: Method, path: &str, token: Option<&str>, body: &str) -> (StatusCode, Value) {
+        let mut request = hyper::Request::builder()
+            .method(method)
+            .uri(format!("http://{}{}", self.address, path))
+            .header("content-type", "application/json");
+        if let Some(token) = token {
+            request = request.header("authorization", token);
+        }
+        let request = request.body(Body::from(body.to_owned())).expect("valid request");
+        let response = Client::new().request(request).await.expect("response from server");
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body()).await.expect("body of response");
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    /// Send SIGTERM, as `docker stop` does
+    pub fn terminate(&self) {
+        let status = Command::new("kill").arg("-TERM").arg(self.process.id().to_string()).status().expect("kill executed");
+        assert!(status.success());
+    }
+
+    /// Wait until the server exits, its log is returned when it was started with `start_with`
+    pub fn stop(&mut self) -> (ExitStatus, String) {
+        let mut log = String::new();
+        if let Some(mut output) = self.process.stdout.take() {
+            output.read_to_string(&mut log).expect("server log");
+        }
+        (self.process.wait().expect("server exited"), log)
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Attribute of an entity response by key
+pub fn attribute<'a>(entity: &'a Value, key: &str) -> &'a Value {
+    entity["attributes"].as_array()
+        .and_then(|attributes| attributes.iter().find(|attribute| attribute["key"] == key))
+        .unwrap_or(&Value::Null)
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+
+use adempiere_backend_rs::config::Config;
+use log::LevelFilter;
+
+fn variables(values: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let values: HashMap<String, String> = values.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+    move |name| values.get(name).cloned()
+}
+
+#[test]
+fn config_file_is_read() {
+    let config = Config::from_file("config.example.toml").expect("valid example");
+    assert_eq!(config.server.port, 7878);
+    assert_eq!(config.middleware.host.as_deref(), Some("http://0.0.0.0:50059"));
+    assert_eq!(config.limits.max_page_size, 500);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn environment_overrides_the_file() {
+    let mut config = Config::from_toml(r#"
+        [middleware]
+        host = "http://middleware:50059"
+        timeout = 30
+
+        [limits]
+        max_page_size = 100
+    "#).expect("valid config");
+    config.apply_env(variables(&[
+        ("MIDDLEWARE_HOST", "http://localhost:50059"),
+        ("MAX_PAGE_SIZE", "50"),
+        ("JWT_AUDIENCE", "adempiere, erp"),
+        ("MIDDLEWARE_HEALTH_TIMEOUT", "1")
+    ])).expect("valid variables");
+    assert_eq!(config.middleware.host.as_deref(), Some("http://localhost:50059"));
+    assert_eq!(config.middleware.timeout, Some(30));
+    assert_eq!(config.limits.max_page_size, 50);
+    assert_eq!(config.auth.audience, vec!["adempiere", "erp"]);
+    assert_eq!(config.middleware.health_timeout, 1);
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    let error = Config::from_toml("[middleware]\nhots = \"http://localhost:50059\"").expect_err("unknown key");
+    assert!(error.contains("hots"));
+}
+
+#[test]
+fn invalid_variables_are_rejected() {
+    let error = Config::default().apply_env(variables(&[("MIDDLEWARE_TIMEOUT", "ten")])).expect_err("invalid timeout");
+    assert!(error.contains("MIDDLEWARE_TIMEOUT"));
+}
+
+#[test]
+fn validation_reports_every_error() {
+    let mut config = Config::default();
+    config.apply_env(variables(&[
+        ("HOST", "localhost"),
+        ("MAX_PAGE_SIZE", "0"),
+        ("LOG_LEVEL", "verbose")
+    ])).expect("valid variables");
+    let error = config.validate().expect_err("invalid config");
+    assert!(error.contains("MIDDLEWARE_HOST"));
+    assert!(error.contains("HOST"));
+    assert!(error.contains("MAX_PAGE_SIZE"));
+    assert!(error.contains("LOG_LEVEL"));
+}
+
+#[test]
+fn timezone_must_be_known() {
+    let mut config = Config::from_toml("[server]\ntimezone = \"America/Caracas\"\n[middleware]\nhost = \"http://localhost:50059\"").expect("valid config");
+    config.validate().expect("known time zone");
+    config.apply_env(variables(&[("TZ", "America/Atlantis")])).expect("valid variables");
+    assert_eq!(config.server.timezone.as_deref(), Some("America/Atlantis"));
+    let error = config.validate().expect_err("unknown time zone");
+    assert!(error.contains("TZ"), "{}", error);
+}
+
+#[test]
+fn rust_log_filter_is_ignored_with_a_warning() {
+    let mut config = Config::default();
+    config.apply_env(variables(&[
+        ("MIDDLEWARE_HOST", "http://localhost:50059"),
+        ("RUST_LOG", "info,hyper=warn")
+    ])).expect("valid variables");
+    config.validate().expect("valid config");
+    assert_eq!(config.log_level(), LevelFilter::Info);
+    assert_eq!(config.warnings.len(), 1);
+    assert!(config.warnings[0].contains("info,hyper=warn"));
+    let mut config = Config::default();
+    config.apply_env(variables(&[("RUST_LOG", "debug")])).expect("valid variables");
+    assert_eq!(config.log_level(), LevelFilter::Debug);
+    assert!(config.warnings.is_empty());
+    config.apply_env(variables(&[("LOG_LEVEL", "warn"), ("RUST_LOG", "trace")])).expect("valid variables");
+    assert_eq!(config.log_level(), LevelFilter::Warn);
+}
+
+#[test]
+fn server_exits_with_an_invalid_configuration() {
+    let directory = std::env::temp_dir();
+    let output = Command::new(env!("CARGO_BIN_EXE_server"))
+        .current_dir(directory)
+        .env_remove("CONFIG_FILE")
+        .env_remove("MIDDLEWARE_HOST")
+        .env("HOST", "127.0.0.1:0")
+        .stdout(Stdio::null())
+        .output()
+        .expect("server finished");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("MIDDLEWARE_HOST"));
+}
+
+#[test]
+fn server_exits_when_the_address_is_in_use() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("free local port");
+    let address = listener.local_addr().expect("local address").to_string();
+    let output = Command::new(env!("CARGO_BIN_EXE_server"))
+        .current_dir(std::env::temp_dir())
+        .env_remove("CONFIG_FILE")
+        .env_remove("TLS_CERT_FILE")
+        .env_remove("TLS_KEY_FILE")
+        .env("MIDDLEWARE_HOST", "http://127.0.0.1:50059")
+        .env("HOST", &address)
+        .output()
+        .expect("server finished");
+    assert!(!output.status.success());
+    let log = String::from_utf8_lossy(&output.stdout);
+    assert!(log.contains(&format!("Server Address {} can not be used", address)), "{}", log);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+}
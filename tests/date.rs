@@ -0,0 +1,101 @@
+use adempiere_backend_rs::models::date::{from_grpc_date_in, parse_date_in, to_grpc_date_in, DateValue};
+use chrono::{FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone};
+
+/// America/Caracas, without daylight saving time
+fn caracas() -> FixedOffset {
+    FixedOffset::west_opt(4 * 3600).expect("valid offset")
+}
+
+/// America/New_York in 2023: daylight saving time (-04:00) from 2023-03-12 07:00 UTC
+/// to 2023-11-05 06:00 UTC, standard time (-05:00) otherwise
+#[derive(Debug, Clone, Copy)]
+struct NewYork2023;
+
+fn utc(month: u32, day: u32, hour: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2023, month, day).and_then(|date| date.and_hms_opt(hour, 0, 0)).expect("valid date time")
+}
+
+impl TimeZone for NewYork2023 {
+    type Offset = FixedOffset;
+
+    fn from_offset(_: &FixedOffset) -> Self {
+        NewYork2023
+    }
+
+    fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+        self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).expect("valid time"))
+    }
+
+    fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+        let daylight = FixedOffset::west_opt(4 * 3600).expect("valid offset");
+        let standard = FixedOffset::west_opt(5 * 3600).expect("valid offset");
+        let offsets: Vec<FixedOffset> = [daylight, standard].into_iter()
+            .filter(|offset| self.offset_from_utc_datetime(&(*local - *offset)) == *offset)
+            .collect();
+        match offsets[..] {
+            [offset] => LocalResult::Single(offset),
+            [first, second] => LocalResult::Ambiguous(first, second),
+            _ => LocalResult::None
+        }
+    }
+
+    fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+        self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).expect("valid time"))
+    }
+
+    fn offset_from_utc_datetime(&self, date_time: &NaiveDateTime) -> FixedOffset {
+        if *date_time >= utc(3, 12, 7) && *date_time < utc(11, 5, 6) {
+            return FixedOffset::west_opt(4 * 3600).expect("valid offset");
+        }
+        FixedOffset::west_opt(5 * 3600).expect("valid offset")
+    }
+}
+
+#[test]
+fn dates_are_kept_without_time_zone() {
+    let date = NaiveDate::from_ymd_opt(2026, 1, 31).expect("valid date");
+    assert_eq!(parse_date_in("2026-01-31", &caracas()), Some(DateValue::Date(date)));
+    assert_eq!(to_grpc_date_in("2026-01-31", &caracas()).as_deref(), Some("2026-01-31"));
+    assert_eq!(from_grpc_date_in("2026-01-31".to_owned(), &caracas()), "2026-01-31");
+    assert_eq!(to_grpc_date_in("31/01/2026", &caracas()), None);
+    assert_eq!(from_grpc_date_in("31/01/2026".to_owned(), &caracas()), "31/01/2026");
+}
+
+#[test]
+fn date_time_with_offset_is_converted_to_the_time_zone() {
+    assert_eq!(to_grpc_date_in("2026-01-31T10:00:00+02:00", &caracas()).as_deref(), Some("2026-01-31 04:00:00"));
+    assert_eq!(from_grpc_date_in("2026-01-31T10:00:00+02:00".to_owned(), &caracas()), "2026-01-31T04:00:00-04:00");
+}
+
+#[test]
+fn utc_date_time_is_converted_to_the_time_zone() {
+    assert_eq!(from_grpc_date_in("2026-01-31T10:00:00Z".to_owned(), &caracas()), "2026-01-31T06:00:00-04:00");
+    assert_eq!(to_grpc_date_in("2026-01-31T10:00:00Z", &caracas()).as_deref(), Some("2026-01-31 06:00:00"));
+}
+
+#[test]
+fn date_time_without_offset_is_read_in_the_time_zone() {
+    assert_eq!(to_grpc_date_in("2026-01-31T10:00", &caracas()).as_deref(), Some("2026-01-31 10:00:00"));
+    assert_eq!(from_grpc_date_in("2026-01-31 10:00:00".to_owned(), &caracas()), "2026-01-31T10:00:00-04:00");
+}
+
+#[test]
+fn epoch_milliseconds_are_rendered_in_the_time_zone() {
+    assert_eq!(from_grpc_date_in("1769853600000".to_owned(), &caracas()), "2026-01-31T06:00:00-04:00");
+    assert_eq!(from_grpc_date_in("1689595200000".to_owned(), &NewYork2023), "2023-07-17T08:00:00-04:00");
+}
+
+#[test]
+fn time_in_a_daylight_saving_gap_is_invalid() {
+    assert_eq!(parse_date_in("2023-03-12 02:30:00", &NewYork2023), None);
+    assert_eq!(to_grpc_date_in("2023-03-12 02:30:00", &NewYork2023), None);
+    assert_eq!(from_grpc_date_in("2023-03-12 02:30:00".to_owned(), &NewYork2023), "2023-03-12 02:30:00");
+    assert_eq!(from_grpc_date_in("2023-03-12 03:30:00".to_owned(), &NewYork2023), "2023-03-12T03:30:00-04:00");
+}
+
+#[test]
+fn ambiguous_time_uses_the_first_occurrence() {
+    assert_eq!(from_grpc_date_in("2023-11-05 01:30:00".to_owned(), &NewYork2023), "2023-11-05T01:30:00-04:00");
+    assert_eq!(to_grpc_date_in("2023-11-05T01:30:00", &NewYork2023).as_deref(), Some("2023-11-05 01:30:00"));
+    assert_eq!(from_grpc_date_in("2023-11-05T06:30:00Z".to_owned(), &NewYork2023), "2023-11-05T01:30:00-05:00");
+}
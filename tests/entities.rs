@@ -0,0 +1,435 @@
+mod common;
+
+use std::time::Duration;
+
+use adempiere_backend_rs::mock::{MockMiddleware, FIRST_ID};
+use adempiere_backend_rs::middleware::ValueType;
+use common::{attribute, TestServer, TOKEN};
+use hyper::{Method, StatusCode};
+use serde_json::json;
+
+#[tokio::test]
+async fn create_entity_returns_the_new_record() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "M_Product_Class",
+            "attributes": [
+                {"key": "Name", "string_value": "Test from Rest API", "value_type": "STRING"},
+                {"key": "IsDefault", "boolean_value": false, "value_type": "BOOLEAN"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["id"], FIRST_ID);
+    assert_eq!(body["table_name"], "M_Product_Class");
+    assert_eq!(attribute(&body, "Name")["string_value"], "Test from Rest API");
+    assert_eq!(attribute(&body, "IsDefault")["boolean_value"], false);
+    assert_eq!(server.mock.record_count("M_Product_Class"), 1);
+}
+
+#[tokio::test]
+async fn create_entity_converts_value_types() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "C_Order",
+            "attributes": [
+                {"key": "C_BPartner_ID", "integer_value": 1000001},
+                {"key": "GrandTotal", "decimal_value": "10.50", "value_type": "DECIMAL"},
+                {"key": "Qty", "decimal_value": 2.5},
+                {"key": "DateOrdered", "date_value": "2026-01-31", "value_type": "DATE"},
+                {"key": "Description", "value_type": "NULL"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let record = server.mock.record("C_Order", FIRST_ID).expect("record stored");
+    assert_eq!(record["C_BPartner_ID"].integer_value, 1000001);
+    assert_eq!(record["C_BPartner_ID"].value_type, ValueType::Integer as i32);
+    let grand_total = record["GrandTotal"].decimal_value.clone().expect("decimal value");
+    assert_eq!(grand_total.decimal_value, "10.50");
+    assert_eq!(grand_total.scale, 2);
+    assert_eq!(record["Qty"].decimal_value.clone().expect("decimal value").decimal_value, "2.5");
+    assert_eq!(record["DateOrdered"].date_value, "2026-01-31");
+    assert!(!record.contains_key("Description"));
+    assert_eq!(attribute(&body, "GrandTotal")["decimal_value"], "10.50");
+    assert_eq!(attribute(&body, "DateOrdered")["date_value"], "2026-01-31");
+}
+
+#[tokio::test]
+async fn create_entity_accepts_plain_json_attributes() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities/C_BPartner?format=flat", Some(TOKEN), Some(json!({
+        "attributes": {
+            "Name": "Test from Rest API",
+            "IsDefault": true,
+            "C_BP_Group_ID": 103,
+            "SO_CreditLimit": "1500.00"
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["attributes"], json!({
+        "Name": "Test from Rest API",
+        "IsDefault": true,
+        "C_BP_Group_ID": 103,
+        "SO_CreditLimit": "1500.00"
+    }));
+}
+
+#[tokio::test]
+async fn create_entity_rejects_a_value_type_mismatch() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "M_Product_Class",
+            "attributes": [
+                {"key": "Name", "integer_value": 10, "value_type": "STRING"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_ARGUMENT");
+    assert_eq!(body["details"]["key"], "Name");
+    assert_eq!(server.mock.record_count("M_Product_Class"), 0);
+}
+
+#[tokio::test]
+async fn create_entity_rejects_a_value_type_without_value() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "M_Product_Class",
+            "attributes": [
+                {"key": "Name", "value_type": "STRING"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"]["key"], "Name");
+    assert_eq!(body["details"]["value_type"], "STRING");
+    assert_eq!(server.mock.record_count("M_Product_Class"), 0);
+}
+
+#[tokio::test]
+async fn create_entity_rejects_an_attribute_without_value() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    for attribute in [json!({"key": "Name"}), json!({"key": "Name", "strng_value": "Test"})] {
+        let (status, body) = server.request(Method::POST, "/v1/entities?lenient=true", Some(TOKEN), Some(json!({
+            "entity": {
+                "table_name": "M_Product_Class",
+                "attributes": [attribute]
+            }
+        }))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "INVALID_ARGUMENT");
+    }
+    assert_eq!(server.mock.record_count("M_Product_Class"), 0);
+}
+
+#[tokio::test]
+async fn create_entity_rejects_a_malformed_body_with_the_error_envelope() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let bodies = [
+        r#"{"entity": {"table_name": "C_Order""#,
+        r#"{"entity": {"table_name": "C_Order", "attributes": [{"key": "C_BPartner_ID", "integer_value": "12"}]}}"#,
+        r#"{"entity": {"table_name": "C_Order", "attributes": [{"key": "GrandTotal", "decimal_value": true}]}}"#,
+        ""
+    ];
+    for body in bodies {
+        let (status, response) = server.request_raw(Method::POST, "/v1/entities", Some(TOKEN), body).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+        assert_eq!(response["code"], "INVALID_ARGUMENT", "{}", body);
+        assert!(response["message"].is_string());
+        assert!(response["request_id"].is_string());
+    }
+    let (status, response) = server.request(Method::POST, "/v1/process", Some(TOKEN), None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(response["code"], "INVALID_ARGUMENT");
+    assert_eq!(server.mock.record_count("C_Order"), 0);
+}
+
+#[tokio::test]
+async fn create_entity_reports_the_unknown_field_of_an_attribute() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let body = r#"{"entity": {"table_name": "C_Order", "attributes": [{"key": "Description", "strng_value": "Test"}]}}"#;
+    let (status, response) = server.request_raw(Method::POST, "/v1/entities", Some(TOKEN), body).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let error = response["details"]["error"].as_str().unwrap_or_default();
+    assert!(error.contains("unknown field `strng_value`"), "{}", response);
+    assert_eq!(server.mock.record_count("C_Order"), 0);
+}
+
+#[tokio::test]
+async fn create_entity_rejects_an_invalid_decimal() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "C_Order",
+            "attributes": [
+                {"key": "GrandTotal", "decimal_value": "ten", "value_type": "DECIMAL"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Invalid Decimal Value");
+}
+
+#[tokio::test]
+async fn create_entity_requires_table_name() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "attributes": []
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["message"], "Table Is Mandatory");
+    assert_eq!(body["details"]["field"], "table_name");
+}
+
+#[tokio::test]
+async fn create_entity_requires_attributes() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "M_Product_Class"
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"]["field"], "attributes");
+}
+
+#[tokio::test]
+async fn create_entity_requires_token() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", None, Some(json!({
+        "entity": {
+            "table_name": "M_Product_Class",
+            "attributes": []
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "UNAUTHENTICATED");
+    assert!(body["request_id"].is_string());
+}
+
+#[tokio::test]
+async fn create_entity_returns_upstream_errors() {
+    let server = TestServer::start(MockMiddleware::new().with_mandatory("C_BPartner", &["Name"])).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "C_BPartner",
+            "attributes": [
+                {"key": "Value", "string_value": "Test", "value_type": "STRING"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "INVALID_ARGUMENT");
+    assert!(body["message"].as_str().unwrap_or_default().contains("Name"));
+}
+
+#[tokio::test]
+async fn create_entity_without_middleware_is_unavailable() {
+    let server = TestServer::start_without_middleware().await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "M_Product_Class",
+            "attributes": []
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["code"], "UNAVAILABLE");
+}
+
+#[tokio::test]
+async fn create_entity_times_out_with_deadline_exceeded() {
+    let server = TestServer::start_with(MockMiddleware::new().with_latency(Duration::from_secs(3)), &[("MIDDLEWARE_TIMEOUT", "1")]).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "M_Product_Class",
+            "attributes": []
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(body["code"], "DEADLINE_EXCEEDED");
+    assert!(body["request_id"].is_string());
+}
+
+#[tokio::test]
+async fn update_entity_changes_the_record() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    server.request(Method::POST, "/v1/entities/C_BPartner", Some(TOKEN), Some(json!({
+        "attributes": {"Name": "Before", "Description": "Removed"}
+    }))).await;
+    let (status, body) = server.request(Method::PATCH, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "C_BPartner",
+            "id": FIRST_ID,
+            "attributes": [
+                {"key": "Name", "string_value": "After", "value_type": "STRING"},
+                {"key": "Description", "value_type": "NULL"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(attribute(&body, "Name")["string_value"], "After");
+    let record = server.mock.record("C_BPartner", FIRST_ID).expect("record stored");
+    assert_eq!(record["Name"].string_value, "After");
+    assert!(!record.contains_key("Description"));
+}
+
+#[tokio::test]
+async fn update_entity_requires_id() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::PATCH, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "C_BPartner",
+            "attributes": []
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"]["field"], "id");
+}
+
+#[tokio::test]
+async fn update_entity_not_found() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::PATCH, "/v1/entities/C_BPartner/10", Some(TOKEN), Some(json!({
+        "attributes": {"Name": "After"}
+    }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn delete_entity_removes_the_record() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    server.request(Method::POST, "/v1/entities/C_BPartner", Some(TOKEN), Some(json!({
+        "attributes": {"Name": "Test"}
+    }))).await;
+    let (status, _) = server.request(Method::DELETE, "/v1/entities", Some(TOKEN), Some(json!({
+        "entity": {
+            "table_name": "C_BPartner",
+            "id": FIRST_ID
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(server.mock.record_count("C_BPartner"), 0);
+    let (status, _) = server.request(Method::GET, &format!("/v1/entities/C_BPartner/{}", FIRST_ID), Some(TOKEN), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_entity_not_found() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::DELETE, "/v1/entities/C_BPartner/10", Some(TOKEN), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "NOT_FOUND");
+}
+
+#[tokio::test]
+async fn delete_entity_requires_token() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, _) = server.request(Method::DELETE, "/v1/entities/C_BPartner/10", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+async fn delete_entities_of(mock: MockMiddleware) {
+    let server = TestServer::start(mock).await;
+    for name in ["First", "Second"] {
+        server.request(Method::POST, "/v1/entities/C_BPartner", Some(TOKEN), Some(json!({
+            "attributes": {"Name": name}
+        }))).await;
+    }
+    let (status, body) = server.request(Method::POST, "/v1/entities/delete", Some(TOKEN), Some(json!({
+        "entities": {
+            "table_name": "C_BPartner",
+            "ids": [FIRST_ID, FIRST_ID + 10, FIRST_ID + 1]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["table_name"], "C_BPartner");
+    assert_eq!(body["deleted"], 2);
+    assert_eq!(body["failed"], 1);
+    assert_eq!(body["results"][0]["id"], FIRST_ID);
+    assert_eq!(body["results"][0]["is_deleted"], true);
+    assert_eq!(body["results"][1]["id"], FIRST_ID + 10);
+    assert_eq!(body["results"][1]["is_deleted"], false);
+    assert_eq!(body["results"][1]["code"], "NOT_FOUND");
+    assert!(body["results"][1]["message"].is_string());
+    assert_eq!(body["results"][2]["is_deleted"], true);
+    assert_eq!(server.mock.record_count("C_BPartner"), 0);
+}
+
+#[tokio::test]
+async fn delete_entities_reports_the_result_of_each_id() {
+    delete_entities_of(MockMiddleware::new()).await;
+}
+
+#[tokio::test]
+async fn delete_entities_deletes_one_by_one_without_bulk_delete() {
+    delete_entities_of(MockMiddleware::new().without_bulk_delete()).await;
+}
+
+#[tokio::test]
+async fn delete_entities_of_a_table_without_records() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities/delete", Some(TOKEN), Some(json!({
+        "entities": {
+            "table_name": "C_BPartner",
+            "ids": [FIRST_ID]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["failed"], 1);
+    assert_eq!(body["results"][0]["code"], "NOT_FOUND");
+    let (status, body) = server.request(Method::POST, "/v1/entities/C_BPartner", Some(TOKEN), Some(json!({
+        "attributes": {"Name": "Test"}
+    }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["id"], FIRST_ID);
+}
+
+#[tokio::test]
+async fn delete_entities_requires_ids() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    let (status, body) = server.request(Method::POST, "/v1/entities/delete", Some(TOKEN), Some(json!({
+        "entities": {
+            "table_name": "C_BPartner",
+            "ids": []
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"]["field"], "ids");
+}
+
+#[tokio::test]
+async fn delete_entities_only_accepts_post() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    for method in [Method::GET, Method::PATCH, Method::DELETE] {
+        let (status, body) = server.request(method.clone(), "/v1/entities/delete", Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED, "{}", method);
+        assert_eq!(body["details"]["allow"], "POST");
+    }
+    assert_eq!(server.mock.record_count("delete"), 0);
+}
+
+#[tokio::test]
+async fn list_entities_filters_and_sorts() {
+    let server = TestServer::start(MockMiddleware::new()).await;
+    for name in ["Banana", "Apple", "Cherry"] {
+        server.request(Method::POST, "/v1/entities/M_Product", Some(TOKEN), Some(json!({
+            "attributes": {"Name": name}
+        }))).await;
+    }
+    let (status, body) = server.request(Method::GET, "/v1/entities/M_Product?sort_by=-Name&page_size=2&format=flat", Some(TOKEN), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["record_count"], 3);
+    assert_eq!(body["records"][0]["attributes"]["Name"], "Cherry");
+    assert_eq!(body["records"][1]["attributes"]["Name"], "Banana");
+    let (status, body) = server.request(Method::GET, "/v1/entities/M_Product?Name%5Bne%5D=Apple&format=flat", Some(TOKEN), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["record_count"], 2);
+}
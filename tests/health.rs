@@ -0,0 +1,72 @@
+use adempiere_backend_rs::auth::TokenValidator;
+use adempiere_backend_rs::channel::connect_lazy;
+use adempiere_backend_rs::mock::MockMiddleware;
+use adempiere_backend_rs::server::{build_router, RouterConfig};
+use salvo::prelude::*;
+use salvo::test::{ResponseExt, TestClient};
+use serde_json::Value;
+use tonic_health::ServingStatus;
+
+async fn service(mock: MockMiddleware) -> Service {
+    let middleware_address = mock.spawn().await.expect("mock middleware started");
+    let channel = connect_lazy(format!("http://{}", middleware_address)).expect("valid middleware host");
+    Service::new(build_router(RouterConfig::new(channel)))
+}
+
+/// Router with a channel to a port where nothing is listening
+fn service_without_middleware() -> Service {
+    let address = std::net::TcpListener::bind("127.0.0.1:0").and_then(|listener| listener.local_addr()).expect("free port");
+    let channel = connect_lazy(format!("http://{}", address)).expect("valid middleware host");
+    Service::new(build_router(RouterConfig::new(channel)))
+}
+
+async fn get(service: &Service, path: &str) -> (StatusCode, Value) {
+    let mut response = TestClient::get(format!("http://127.0.0.1:7878{}", path)).send(service).await;
+    let body: Value = response.take_json().await.expect("JSON body");
+    (response.status_code.expect("status code"), body)
+}
+
+#[tokio::test]
+async fn live_does_not_call_middleware() {
+    let (status, body) = get(&service_without_middleware(), "/health/live").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "UP");
+}
+
+#[tokio::test]
+async fn ready_when_middleware_is_serving() {
+    let (status, body) = get(&service(MockMiddleware::new()).await, "/health/ready").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "UP");
+    assert_eq!(body["middleware"]["status"], "SERVING");
+    assert!(body["middleware"]["latency_ms"].is_u64());
+}
+
+#[tokio::test]
+async fn not_ready_when_middleware_is_not_serving() {
+    let (status, body) = get(&service(MockMiddleware::new().with_health_status(ServingStatus::NotServing)).await, "/health/ready").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "DOWN");
+    assert_eq!(body["middleware"]["status"], "NOT_SERVING");
+}
+
+#[tokio::test]
+async fn not_ready_when_middleware_is_unreachable() {
+    let (status, body) = get(&service_without_middleware(), "/health/ready").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["status"], "DOWN");
+    assert_eq!(body["middleware"]["ready"], false);
+    assert!(body["middleware"]["message"].is_string());
+}
+
+#[tokio::test]
+async fn health_does_not_require_a_token() {
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let channel = connect_lazy(format!("http://{}", middleware_address)).expect("valid middleware host");
+    let validator = TokenValidator::new(Some(b"secret"), None, Vec::new()).expect("valid validator");
+    let service = Service::new(build_router(RouterConfig::new(channel).with_token_validator(validator)));
+    let (status, _) = get(&service, "/health/ready").await;
+    assert_eq!(status, StatusCode::OK);
+    let response = TestClient::get("http://127.0.0.1:7878/v1/entities/C_BPartner").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::UNAUTHORIZED));
+}
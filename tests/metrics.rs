@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use adempiere_backend_rs::channel::{connect_lazy_with_metrics, middleware_endpoint, DEFAULT_CONNECT_TIMEOUT};
+use adempiere_backend_rs::metrics::Metrics;
+use adempiere_backend_rs::mock::MockMiddleware;
+use adempiere_backend_rs::server::{build_router, RouterConfig};
+use salvo::prelude::*;
+use salvo::test::{ResponseExt, TestClient};
+use serde_json::{json, Value};
+
+fn service(middleware_host: String) -> Service {
+    service_with_connect_timeout(middleware_host, DEFAULT_CONNECT_TIMEOUT)
+}
+
+fn service_with_connect_timeout(middleware_host: String, connect_timeout: Duration) -> Service {
+    let metrics = Metrics::new();
+    let endpoint = middleware_endpoint(middleware_host).expect("valid middleware host").connect_timeout(connect_timeout);
+    let channel = connect_lazy_with_metrics(&endpoint, connect_timeout, metrics.clone());
+    Service::new(build_router(RouterConfig::new(channel).with_metrics(metrics)))
+}
+
+async fn metrics(service: &Service) -> String {
+    let mut response = TestClient::get("http://127.0.0.1:7878/metrics").send(service).await;
+    assert_eq!(response.status_code, Some(StatusCode::OK));
+    assert!(response.headers().get("content-type").and_then(|value| value.to_str().ok()).unwrap_or_default().starts_with("text/plain"));
+    response.take_string().await.expect("text body")
+}
+
+#[tokio::test]
+async fn requests_are_counted_by_route_and_table() {
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let service = service(format!("http://{}", middleware_address));
+    let mut response = TestClient::post("http://127.0.0.1:7878/v1/entities")
+        .add_header("authorization", "Bearer test", true)
+        .json(&json!({"entity": {"table_name": "C_BPartner", "attributes": [{"key": "Name", "string_value": "Test", "value_type": "STRING"}]}}))
+        .send(&service)
+        .await;
+    assert_eq!(response.status_code, Some(StatusCode::OK));
+    let body: Value = response.take_json().await.expect("JSON body");
+    let response = TestClient::get(format!("http://127.0.0.1:7878/v1/entities/C_BPartner/{}", body["id"]))
+        .add_header("authorization", "Bearer test", true)
+        .send(&service)
+        .await;
+    assert_eq!(response.status_code, Some(StatusCode::OK));
+    let content = metrics(&service).await;
+    assert!(content.contains(r#"adempiere_backend_http_requests_total{method="POST",process_code="",route="/v1/entities",status="200",table_name="c_bpartner"} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_http_requests_total{method="GET",process_code="",route="/v1/entities/<table_name>/<id>",status="200",table_name="c_bpartner"} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_http_request_duration_seconds_count{method="GET",process_code="",route="/v1/entities/<table_name>/<id>",status="200",table_name="c_bpartner"} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_middleware_requests_total{code="OK",method="CreateEntity"} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_middleware_request_duration_seconds_count{code="OK",method="GetEntity"} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_middleware_connections_total{result="success"} 1"#), "{}", content);
+}
+
+#[tokio::test]
+async fn errors_are_counted_by_code_and_process() {
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let service = service(format!("http://{}", middleware_address));
+    let response = TestClient::get("http://127.0.0.1:7878/v1/entities/C_BPartner").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::UNAUTHORIZED));
+    let response = TestClient::post("http://127.0.0.1:7878/v1/process")
+        .add_header("authorization", "Bearer test", true)
+        .json(&json!({"process": {"process_code": "Unknown_Process"}}))
+        .send(&service)
+        .await;
+    assert_eq!(response.status_code, Some(StatusCode::NOT_FOUND));
+    let content = metrics(&service).await;
+    assert!(content.contains(r#"adempiere_backend_http_request_errors_total{code="UNAUTHENTICATED",method="GET",process_code="",route="/v1/entities/<table_name>",status="401",table_name=""} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_http_request_errors_total{code="NOT_FOUND",method="POST",process_code="other",route="/v1/process",status="404",table_name=""} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_middleware_requests_total{code="NOT_FOUND",method="RunBusinessProcess"} 1"#), "{}", content);
+}
+
+#[tokio::test]
+async fn tables_not_found_are_counted_as_other() {
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let service = service(format!("http://{}", middleware_address));
+    for table_name in ["RandomA", "RandomB", "RandomC"] {
+        let response = TestClient::get(format!("http://127.0.0.1:7878/v1/entities/{}/1", table_name))
+            .add_header("authorization", "Bearer x", true)
+            .send(&service)
+            .await;
+        assert_eq!(response.status_code, Some(StatusCode::NOT_FOUND));
+    }
+    let content = metrics(&service).await;
+    assert!(content.contains(r#"adempiere_backend_http_requests_total{method="GET",process_code="",route="/v1/entities/<table_name>/<id>",status="404",table_name="other"} 3"#), "{}", content);
+    assert!(!content.to_lowercase().contains("random"), "{}", content);
+}
+
+#[tokio::test]
+async fn failed_connections_are_counted() {
+    let address = std::net::TcpListener::bind("127.0.0.1:0").and_then(|listener| listener.local_addr()).expect("free port");
+    let service = service(format!("http://{}", address));
+    let response = TestClient::get("http://127.0.0.1:7878/v1/entities/C_BPartner/1")
+        .add_header("authorization", "Bearer test", true)
+        .send(&service)
+        .await;
+    assert_eq!(response.status_code, Some(StatusCode::SERVICE_UNAVAILABLE));
+    let content = metrics(&service).await;
+    assert!(content.contains(r#"adempiere_backend_middleware_connections_total{result="failure"}"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_middleware_requests_total{code="UNAVAILABLE",method="GetEntity"} 1"#), "{}", content);
+    assert!(content.contains(r#"adempiere_backend_http_request_errors_total{code="UNAVAILABLE",method="GET",process_code="",route="/v1/entities/<table_name>/<id>",status="503",table_name="other"} 1"#), "{}", content);
+}
+
+#[tokio::test]
+async fn connections_to_an_unreachable_host_time_out() {
+    let service = service_with_connect_timeout("http://10.255.255.1:50059".to_owned(), Duration::from_millis(500));
+    let started = Instant::now();
+    let response = TestClient::get("http://127.0.0.1:7878/v1/entities/C_BPartner/1")
+        .add_header("authorization", "Bearer test", true)
+        .send(&service)
+        .await;
+    assert!(response.status_code.map(|status| status.is_server_error()).unwrap_or_default(), "{:?}", response.status_code);
+    assert!(started.elapsed() < Duration::from_secs(3), "{:?}", started.elapsed());
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use adempiere_backend_rs::mock::MockMiddleware;
+use adempiere_backend_rs::middleware::{ProcessLog, RunBusinessProcessResponse};
+use common::{TestServer, TOKEN};
+use hyper::{Method, StatusCode};
+use serde_json::json;
+
+fn mock() -> MockMiddleware {
+    MockMiddleware::new().with_process("C_Order_Process", RunBusinessProcessResponse {
+        summary: "Process Completed".to_owned(),
+        logs: vec![ProcessLog {
+            record_id: 1000001,
+            log: "Order Completed".to_owned()
+        }],
+        ..Default::default()
+    })
+}
+
+#[tokio::test]
+async fn run_process_returns_summary_and_logs() {
+    let server = TestServer::start(mock()).await;
+    let (status, body) = server.request(Method::POST, "/v1/process", Some(TOKEN), Some(json!({
+        "process": {
+            "process_code": "C_Order_Process",
+            "table_name": "C_Order",
+            "id": 1000001,
+            "parameters": [
+                {"key": "DocAction", "string_value": "CO", "value_type": "STRING"}
+            ],
+            "selections": [
+                {"selection_id": 1000001, "values": [{"key": "Qty", "decimal_value": "1.0"}]}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["is_error"], false);
+    assert_eq!(body["summary"], "Process Completed");
+    assert_eq!(body["logs"][0]["record_id"], 1000001);
+    assert_eq!(body["logs"][0]["log"], "Order Completed");
+}
+
+#[tokio::test]
+async fn run_process_requires_process_code() {
+    let server = TestServer::start(mock()).await;
+    let (status, body) = server.request(Method::POST, "/v1/process", Some(TOKEN), Some(json!({
+        "process": {
+            "table_name": "C_Order"
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"]["field"], "process_code");
+}
+
+#[tokio::test]
+async fn run_process_requires_selection_id() {
+    let server = TestServer::start(mock()).await;
+    let (status, body) = server.request(Method::POST, "/v1/process", Some(TOKEN), Some(json!({
+        "process": {
+            "process_code": "C_Order_Process",
+            "selections": [{"values": []}]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"]["field"], "selections.selection_id");
+}
+
+#[tokio::test]
+async fn run_process_rejects_an_invalid_parameter() {
+    let server = TestServer::start(mock()).await;
+    let (status, body) = server.request(Method::POST, "/v1/process", Some(TOKEN), Some(json!({
+        "process": {
+            "process_code": "C_Order_Process",
+            "parameters": [
+                {"key": "DateFrom", "date_value": "31/01/2026", "value_type": "DATE"}
+            ]
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["details"]["key"], "DateFrom");
+}
+
+#[tokio::test]
+async fn run_process_requires_token() {
+    let server = TestServer::start(mock()).await;
+    let (status, body) = server.request(Method::POST, "/v1/process", None, Some(json!({
+        "process": {
+            "process_code": "C_Order_Process"
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["message"], "Token is Mandatory");
+}
+
+#[tokio::test]
+async fn run_process_not_found() {
+    let server = TestServer::start(mock()).await;
+    let (status, body) = server.request(Method::POST, "/v1/process", Some(TOKEN), Some(json!({
+        "process": {
+            "process_code": "Unknown"
+        }
+    }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "NOT_FOUND");
+}
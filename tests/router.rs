@@ -0,0 +1,114 @@
+use adempiere_backend_rs::channel::connect_lazy;
+use adempiere_backend_rs::mock::MockMiddleware;
+use adempiere_backend_rs::server::openapi::{ApiDocConfig, SWAGGER_UI_VERSION};
+use adempiere_backend_rs::server::{build_router, Limits, RouterConfig};
+use salvo::prelude::*;
+use salvo::test::{ResponseExt, TestClient};
+use serde_json::{json, Value};
+
+#[handler]
+async fn hello(_res: &mut Response) {
+    _res.render(Text::Plain("Hello"));
+}
+
+/// ADempiere endpoints mounted under `erp`, next to a route of the application
+async fn service() -> Service {
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let channel = connect_lazy(format!("http://{}", middleware_address)).expect("valid middleware host");
+    let router = Router::new()
+        .push(Router::with_path("hello").get(hello))
+        .push(Router::with_path("erp").push(build_router(RouterConfig::new(channel))));
+    Service::new(router)
+}
+
+#[tokio::test]
+async fn mounted_router_serves_entities() {
+    let service = service().await;
+    let mut response = TestClient::post("http://127.0.0.1:7878/erp/v1/entities/C_BPartner")
+        .add_header("authorization", "Bearer test", true)
+        .json(&json!({"attributes": {"Name": "Test"}}))
+        .send(&service)
+        .await;
+    assert_eq!(response.status_code, Some(StatusCode::CREATED));
+    let body: Value = response.take_json().await.expect("JSON body");
+    assert_eq!(body["table_name"], "C_BPartner");
+}
+
+#[tokio::test]
+async fn mounted_router_keeps_application_routes() {
+    let service = service().await;
+    let mut response = TestClient::get("http://127.0.0.1:7878/hello").send(&service).await;
+    assert_eq!(response.take_string().await.expect("text body"), "Hello");
+    let response = TestClient::get("http://127.0.0.1:7878/erp/openapi.json").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::OK));
+    let response = TestClient::get("http://127.0.0.1:7878/v1/entities/C_BPartner").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::NOT_FOUND));
+}
+
+#[tokio::test]
+async fn page_size_is_limited_by_the_router_limits() {
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let channel = connect_lazy(format!("http://{}", middleware_address)).expect("valid middleware host");
+    let service = Service::new(build_router(RouterConfig::new(channel).with_limits(Limits {
+        max_page_size: 10
+    })));
+    let mut response = TestClient::get("http://127.0.0.1:7878/v1/entities/C_BPartner?page_size=20")
+        .add_header("authorization", "Bearer test", true)
+        .send(&service)
+        .await;
+    assert_eq!(response.status_code, Some(StatusCode::BAD_REQUEST));
+    let body: Value = response.take_json().await.expect("JSON body");
+    assert_eq!(body["message"], "Page Size must be between 1 and 10");
+}
+
+#[tokio::test]
+async fn openapi_server_is_the_mounted_path() {
+    let service = service().await;
+    let mut response = TestClient::get("http://127.0.0.1:7878/erp/openapi.json").send(&service).await;
+    let body: Value = response.take_json().await.expect("JSON body");
+    assert_eq!(body["servers"][0]["url"], "/erp");
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let channel = connect_lazy(format!("http://{}", middleware_address)).expect("valid middleware host");
+    let service = Service::new(build_router(RouterConfig::new(channel).with_api_doc(ApiDocConfig {
+        server_url: Some("https://erp.example.com/api".to_owned()),
+        ..Default::default()
+    })));
+    let mut response = TestClient::get("http://127.0.0.1:7878/openapi.json").send(&service).await;
+    let body: Value = response.take_json().await.expect("JSON body");
+    assert_eq!(body["servers"][0]["url"], "https://erp.example.com/api");
+}
+
+#[tokio::test]
+async fn swagger_ui_loads_a_pinned_version() {
+    let service = service().await;
+    let mut response = TestClient::get("http://127.0.0.1:7878/erp/swagger-ui").send(&service).await;
+    let page = response.take_string().await.expect("HTML page");
+    assert!(page.contains(&format!(r#"src="https://unpkg.com/swagger-ui-dist@{}/swagger-ui-bundle.js" crossorigin="anonymous""#, SWAGGER_UI_VERSION)), "{}", page);
+    let response = TestClient::get("http://127.0.0.1:7878/erp/swagger-ui/swagger-ui.css").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::NOT_FOUND));
+}
+
+#[tokio::test]
+async fn swagger_ui_files_are_served_from_a_directory() {
+    let directory = std::env::temp_dir().join(format!("swagger-ui-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).expect("directory created");
+    std::fs::write(directory.join("swagger-ui.css"), "body {}").expect("file written");
+    std::fs::write(directory.join("swagger-ui-bundle.js"), "var SwaggerUIBundle;").expect("file written");
+    std::fs::write(directory.join("secret.txt"), "secret").expect("file written");
+    let middleware_address = MockMiddleware::new().spawn().await.expect("mock middleware started");
+    let channel = connect_lazy(format!("http://{}", middleware_address)).expect("valid middleware host");
+    let service = Service::new(build_router(RouterConfig::new(channel).with_api_doc(ApiDocConfig {
+        swagger_ui_assets: Some(directory.clone()),
+        ..Default::default()
+    })));
+    let mut response = TestClient::get("http://127.0.0.1:7878/swagger-ui").send(&service).await;
+    let page = response.take_string().await.expect("HTML page");
+    assert!(page.contains(r#"src="swagger-ui/swagger-ui-bundle.js""#), "{}", page);
+    assert!(!page.contains("unpkg.com"), "{}", page);
+    let mut response = TestClient::get("http://127.0.0.1:7878/swagger-ui/swagger-ui.css").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::OK));
+    assert_eq!(response.take_string().await.expect("CSS file"), "body {}");
+    let response = TestClient::get("http://127.0.0.1:7878/swagger-ui/secret.txt").send(&service).await;
+    assert_eq!(response.status_code, Some(StatusCode::NOT_FOUND));
+    std::fs::remove_dir_all(&directory).ok();
+}
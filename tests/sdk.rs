@@ -0,0 +1,146 @@
+use adempiere_backend_rs::middleware::{ProcessLog, RunBusinessProcessResponse, ValueType};
+use adempiere_backend_rs::mock::{MockMiddleware, FIRST_ID};
+use adempiere_backend_rs::models::documents::{Attributes, EntityResponse};
+use adempiere_backend_rs::sdk::{AdempiereClient, AttributeValue, ClientError};
+use chrono::NaiveDate;
+use serde_json::{json, Map, Value};
+use tonic::Code;
+
+async fn client(mock: MockMiddleware) -> AdempiereClient {
+    let middleware_address = mock.spawn().await.expect("mock middleware started");
+    AdempiereClient::builder()
+        .endpoint(format!("http://{}", middleware_address))
+        .token("test")
+        .build()
+        .expect("valid client")
+}
+
+fn flat_attributes(entity: EntityResponse) -> Map<String, Value> {
+    match entity.into_flat().attributes {
+        Some(Attributes::Object(values)) => values,
+        _ => Map::new()
+    }
+}
+
+#[test]
+fn builder_requires_endpoint_and_token() {
+    let error = AdempiereClient::builder().token("test").build().err().expect("endpoint is mandatory");
+    assert!(matches!(error, ClientError::InvalidEndpoint(_)));
+    assert_eq!(error.code(), Code::InvalidArgument);
+    let error = AdempiereClient::builder().endpoint("http://0.0.0.0:50059").build().err().expect("token is mandatory");
+    assert!(matches!(error, ClientError::InvalidToken));
+    assert_eq!(error.code(), Code::Unauthenticated);
+    for token in ["", "Bearer \n"] {
+        let error = AdempiereClient::builder().endpoint("http://0.0.0.0:50059").token(token).build().err().expect("invalid token");
+        assert!(matches!(error, ClientError::InvalidToken), "{:?}", token);
+    }
+    let error = AdempiereClient::builder().endpoint("not a valid uri").token("test").build().err().expect("invalid endpoint");
+    assert!(matches!(error, ClientError::InvalidEndpoint(_)));
+}
+
+#[test]
+fn attribute_values_from_rust_types() {
+    assert_eq!(AttributeValue::from(10), AttributeValue::Integer(10));
+    assert_eq!(AttributeValue::from(true), AttributeValue::Boolean(true));
+    assert_eq!(AttributeValue::from("Test"), AttributeValue::String("Test".to_owned()));
+    assert_eq!(AttributeValue::from("Test".to_owned()), AttributeValue::String("Test".to_owned()));
+    assert_eq!(AttributeValue::from(NaiveDate::from_ymd_opt(2023, 1, 31).expect("valid date")), AttributeValue::Date("2023-01-31".to_owned()));
+    let date_time = NaiveDate::from_ymd_opt(2023, 1, 31).and_then(|date| date.and_hms_opt(13, 5, 0)).expect("valid date time");
+    assert_eq!(AttributeValue::from(date_time), AttributeValue::Date("2023-01-31 13:05:00".to_owned()));
+    assert_eq!(AttributeValue::from(Some(5)), AttributeValue::Integer(5));
+    assert_eq!(AttributeValue::from(None::<i32>), AttributeValue::Null);
+    assert_eq!(AttributeValue::decimal("0.10"), AttributeValue::Decimal("0.10".to_owned()));
+}
+
+#[tokio::test]
+async fn create_update_get_and_delete() {
+    let mock = MockMiddleware::new();
+    let client = client(mock.clone()).await;
+    let entity = client.create("C_BPartner")
+        .set("Name", "Test from Rust")
+        .set("IsDefault", false)
+        .set("C_BP_Group_ID", 103)
+        .set("CreditLimit", AttributeValue::decimal("1500.50"))
+        .set("FirstSale", NaiveDate::from_ymd_opt(2023, 1, 31).expect("valid date"))
+        .send()
+        .await
+        .expect("entity created");
+    assert_eq!(entity.id, Some(FIRST_ID));
+    let record = mock.record("C_BPartner", FIRST_ID).expect("stored record");
+    assert_eq!(record["Name"].value_type, ValueType::String as i32);
+    assert_eq!(record["IsDefault"].value_type, ValueType::Boolean as i32);
+    assert_eq!(record["C_BP_Group_ID"].integer_value, 103);
+    assert_eq!(record["CreditLimit"].value_type, ValueType::Decimal as i32);
+    assert_eq!(record["FirstSale"].value_type, ValueType::Date as i32);
+
+    let entity = client.update("C_BPartner", FIRST_ID)
+        .set("Name", "Updated from Rust")
+        .set("C_BP_Group_ID", None::<i32>)
+        .send()
+        .await
+        .expect("entity updated");
+    let attributes = flat_attributes(entity);
+    assert_eq!(attributes["Name"], json!("Updated from Rust"));
+    assert!(!attributes.contains_key("C_BP_Group_ID"));
+
+    let attributes = flat_attributes(client.get("C_BPartner", FIRST_ID).await.expect("entity found"));
+    assert_eq!(attributes["Name"], json!("Updated from Rust"));
+    assert_eq!(attributes["CreditLimit"], json!("1500.50"));
+
+    client.delete("C_BPartner", FIRST_ID).await.expect("entity deleted");
+    assert_eq!(mock.record_count("C_BPartner"), 0);
+    let error = client.get("C_BPartner", FIRST_ID).await.expect_err("entity deleted");
+    assert!(error.is_not_found());
+}
+
+#[tokio::test]
+async fn invalid_value_is_not_sent() {
+    let mock = MockMiddleware::new();
+    let client = client(mock.clone()).await;
+    let error = client.create("C_BPartner")
+        .set("CreditLimit", AttributeValue::decimal("one"))
+        .send()
+        .await
+        .expect_err("invalid decimal");
+    match error {
+        ClientError::InvalidValue { key, .. } => assert_eq!(key, "CreditLimit"),
+        error => panic!("unexpected error {:?}", error)
+    }
+    assert_eq!(mock.record_count("C_BPartner"), 0);
+}
+
+#[tokio::test]
+async fn run_process_with_parameters_and_selections() {
+    let client = client(MockMiddleware::new().with_process("M_InOut_CreateInvoice", RunBusinessProcessResponse {
+        summary: "Invoices Generated".to_owned(),
+        logs: vec![ProcessLog {
+            record_id: 1000001,
+            log: "Invoice Created".to_owned()
+        }],
+        ..Default::default()
+    })).await;
+    let response = client.run_process("M_InOut_CreateInvoice")
+        .parameter("DateInvoiced", NaiveDate::from_ymd_opt(2023, 1, 31).expect("valid date"))
+        .table_selected_id(319)
+        .selection(1000001, [("M_InOut_ID", 1000001)])
+        .send()
+        .await
+        .expect("process executed");
+    assert_eq!(response.is_error, Some(false));
+    assert_eq!(response.summary.as_deref(), Some("Invoices Generated"));
+    assert_eq!(response.logs.map(|logs| logs.len()), Some(1));
+    let error = client.run_process("Unknown_Process").send().await.expect_err("unknown process");
+    assert!(error.is_not_found());
+}
+
+#[tokio::test]
+async fn unreachable_middleware_is_unavailable() {
+    let address = std::net::TcpListener::bind("127.0.0.1:0").and_then(|listener| listener.local_addr()).expect("free port");
+    let client = AdempiereClient::builder()
+        .endpoint(format!("http://{}", address))
+        .token("Bearer test")
+        .build()
+        .expect("valid client");
+    let error = client.get("C_BPartner", FIRST_ID).await.expect_err("middleware unavailable");
+    assert!(error.is_unavailable(), "{}", error);
+}
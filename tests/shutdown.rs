@@ -0,0 +1,158 @@
+mod common;
+
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use adempiere_backend_rs::mock::MockMiddleware;
+use common::{TestServer, TOKEN};
+use hyper::{Method, StatusCode};
+use serde_json::json;
+use tokio_rustls::rustls::{Certificate, ClientConfig, ClientConnection, RootCertStore, ServerName, StreamOwned};
+
+const CERTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/certs");
+
+fn create_request() -> serde_json::Value {
+    json!({
+        "entity": {
+            "table_name": "C_Order",
+            "attributes": [
+                {"key": "DocumentNo", "string_value": "1000", "value_type": "STRING"}
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_requests_in_flight() {
+    let mock = MockMiddleware::new().with_latency(Duration::from_millis(800));
+    let mut server = TestServer::start_with(mock, &[("DRAIN_TIMEOUT", "10")]).await;
+    let ((status, _), _) = tokio::join!(
+        server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(create_request())),
+        async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            server.terminate();
+        }
+    );
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(server.mock.record_count("C_Order"), 1);
+    let (exit_status, log) = server.stop();
+    assert!(exit_status.success());
+    assert!(log.contains("All requests in flight were completed"));
+}
+
+#[tokio::test]
+async fn shutdown_logs_aborted_requests() {
+    let mock = MockMiddleware::new().with_latency(Duration::from_secs(5));
+    let mut server = TestServer::start_with(mock, &[("DRAIN_TIMEOUT", "1")]).await;
+    let (response, _) = tokio::join!(
+        server.try_request(Method::POST, "/v1/entities", Some(TOKEN), Some(create_request())),
+        async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            server.terminate();
+        }
+    );
+    assert!(response.is_err(), "{:?}", response);
+    let (exit_status, log) = server.stop();
+    assert!(exit_status.success());
+    assert!(log.contains("Request aborted by shutdown: POST /v1/entities"), "{}", log);
+}
+
+type HttpsStream = StreamOwned<ClientConnection, std::net::TcpStream>;
+
+/// Request over HTTPS trusting the test CA, the response is read by the caller
+fn https_send(address: &str, method: &str, path: &str, body: &str) -> HttpsStream {
+    let mut roots = RootCertStore::empty();
+    let content = std::fs::read(format!("{}/ca.pem", CERTS)).expect("CA file");
+    for certificate in rustls_pemfile::certs(&mut content.as_slice()).expect("PEM certificates") {
+        roots.add(&Certificate(certificate)).expect("CA certificate");
+    }
+    let config = ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_no_client_auth();
+    let connection = ClientConnection::new(Arc::new(config), ServerName::try_from("localhost").expect("server name")).expect("TLS connection");
+    let mut stream = StreamOwned::new(connection, std::net::TcpStream::connect(address).expect("connection"));
+    let request = format!("{} {} HTTP/1.1\r\nHost: localhost\r\nAuthorization: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", method, path, TOKEN, body.len(), body);
+    stream.write_all(request.as_bytes()).expect("request sent");
+    stream
+}
+
+/// Response read up to its `Content-Length`, or what was received before the connection was closed
+fn read_response(mut stream: HttpsStream) -> String {
+    let mut response = Vec::new();
+    let mut length = None;
+    let mut buffer = [0; 65536];
+    loop {
+        if length.is_none() {
+            let text = String::from_utf8_lossy(&response);
+            length = text.split_once("\r\n\r\n").map(|(head, _)| head.len() + 4 + head.lines()
+                .find_map(|line| line.to_lowercase().strip_prefix("content-length: ").and_then(|length| length.parse::<usize>().ok()))
+                .unwrap_or_default());
+        }
+        if matches!(length, Some(length) if response.len() >= length) {
+            break;
+        }
+        match stream.read(&mut buffer) {
+            Ok(0) | Err(_) => break,
+            Ok(read) => response.extend_from_slice(&buffer[..read])
+        }
+    }
+    String::from_utf8_lossy(&response).into_owned()
+}
+
+fn https_variables() -> Vec<(&'static str, String)> {
+    vec![
+        ("DRAIN_TIMEOUT", "10".to_owned()),
+        ("TLS_CERT_FILE", format!("{}/server.pem", CERTS)),
+        ("TLS_KEY_FILE", format!("{}/server.key", CERTS))
+    ]
+}
+
+#[tokio::test]
+async fn https_shutdown_waits_for_requests_in_flight() {
+    let mock = MockMiddleware::new().with_latency(Duration::from_millis(800));
+    let variables = https_variables();
+    let variables: Vec<(&str, &str)> = variables.iter().map(|(key, value)| (*key, value.as_str())).collect();
+    let mut server = TestServer::start_with(mock, &variables).await;
+    let address = server.address().to_owned();
+    let body = create_request().to_string();
+    let (response, _) = tokio::join!(
+        tokio::task::spawn_blocking(move || read_response(https_send(&address, "POST", "/v1/entities", &body))),
+        async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            server.terminate();
+        }
+    );
+    let response = response.expect("request sent");
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.contains(r#""table_name":"C_Order""#), "{}", response);
+    let (exit_status, log) = server.stop();
+    assert!(exit_status.success());
+    assert!(log.contains("All requests in flight were completed"), "{}", log);
+}
+
+/// The handler is done but the response is still being written, the connection is not dropped.
+/// The requests are blocking, the mock middleware runs in other threads
+#[tokio::test(flavor = "multi_thread")]
+async fn https_shutdown_writes_the_whole_response() {
+    let mut variables = https_variables();
+    variables.push(("MAX_BODY_SIZE", "2000000".to_owned()));
+    let variables: Vec<(&str, &str)> = variables.iter().map(|(key, value)| (*key, value.as_str())).collect();
+    let mut server = TestServer::start_with(MockMiddleware::new(), &variables).await;
+    let address = server.address().to_owned();
+    //  Escaped in JSON as `\u0001`: the response is larger than the socket buffers
+    let description = "\u{1}".repeat(200_000);
+    for _ in 0..15 {
+        let body = json!({"attributes": {"Description": description}}).to_string();
+        let response = read_response(https_send(&address, "POST", "/v1/entities/C_Order", &body));
+        assert!(response.starts_with("HTTP/1.1 201"), "{}", response.lines().next().unwrap_or_default());
+    }
+    let stream = https_send(&address, "GET", "/v1/entities/C_Order?page_size=15", "");
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    server.terminate();
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let response = read_response(stream);
+    let body = response.split_once("\r\n\r\n").map(|(_, body)| body).unwrap_or_default();
+    let body: serde_json::Value = serde_json::from_str(body).expect("whole JSON body");
+    assert_eq!(body["records"].as_array().map(|records| records.len()), Some(15));
+    let (exit_status, _) = server.stop();
+    assert!(exit_status.success());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use adempiere_backend_rs::mock::MockMiddleware;
+use common::{TestServer, TOKEN};
+use hyper::{Method, StatusCode};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::{Certificate, ClientConfig, RootCertStore, ServerName};
+use tokio_rustls::TlsConnector;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Identity, Server, ServerTlsConfig};
+
+const CERTS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/certs");
+
+fn cert_path(name: &str) -> String {
+    format!("{}/{}", CERTS, name)
+}
+
+fn certificates(name: &str) -> Vec<Certificate> {
+    let content = fs::read(cert_path(name)).expect("certificate file");
+    rustls_pemfile::certs(&mut content.as_slice()).expect("PEM certificates").into_iter().map(Certificate).collect()
+}
+
+/// GET over HTTPS trusting the test CA, the status line and the certificate of the server are returned
+async fn https_get(address: &str, path: &str) -> (String, Certificate) {
+    let mut roots = RootCertStore::empty();
+    for certificate in certificates("ca.pem") {
+        roots.add(&certificate).expect("CA certificate");
+    }
+    let config = ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_no_client_auth();
+    let stream = TcpStream::connect(address).await.expect("connection");
+    let mut stream = TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("localhost").expect("server name"), stream)
+        .await
+        .expect("TLS handshake");
+    let certificate = stream.get_ref().1.peer_certificates().expect("server certificate")[0].clone();
+    stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n", path).as_bytes()).await.expect("request sent");
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.ok();
+    (response.lines().next().unwrap_or_default().to_owned(), certificate)
+}
+
+/// Mock middleware with TLS, client certificates of the test CA are required
+async fn spawn_tls_middleware(mock: MockMiddleware) -> String {
+    let identity = Identity::from_pem(fs::read(cert_path("server.pem")).unwrap(), fs::read(cert_path("server.key")).unwrap());
+    let client_ca = tonic::transport::Certificate::from_pem(fs::read(cert_path("ca.pem")).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.expect("free local port");
+    let address = listener.local_addr().expect("local address");
+    let server = Server::builder()
+        .tls_config(ServerTlsConfig::new().identity(identity).client_ca_root(client_ca))
+        .expect("valid TLS")
+        .add_service(mock.into_service())
+        .serve_with_incoming(TcpListenerStream::new(listener));
+    tokio::spawn(server);
+    format!("https://{}", address)
+}
+
+fn create_request() -> serde_json::Value {
+    json!({
+        "entity": {
+            "table_name": "C_BPartner",
+            "attributes": [
+                {"key": "Name", "string_value": "Test", "value_type": "STRING"}
+            ]
+        }
+    })
+}
+
+#[tokio::test]
+async fn server_listens_with_https() {
+    let server = TestServer::start_with(MockMiddleware::new(), &[
+        ("TLS_CERT_FILE", &cert_path("server.pem")),
+        ("TLS_KEY_FILE", &cert_path("server.key"))
+    ]).await;
+    let (status, certificate) = https_get(server.address(), "/openapi.json").await;
+    assert_eq!(status, "HTTP/1.1 200 OK");
+    assert_eq!(certificate, certificates("server.pem")[0]);
+}
+
+#[tokio::test]
+async fn server_reloads_a_changed_certificate() {
+    let directory = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("tls-reload");
+    fs::create_dir_all(&directory).expect("temporary directory");
+    let cert_file = directory.join("server.pem");
+    let key_file = directory.join("server.key");
+    fs::copy(cert_path("server.pem"), &cert_file).expect("certificate copied");
+    fs::copy(cert_path("server.key"), &key_file).expect("key copied");
+    let server = TestServer::start_with(MockMiddleware::new(), &[
+        ("TLS_CERT_FILE", cert_file.to_str().unwrap()),
+        ("TLS_KEY_FILE", key_file.to_str().unwrap()),
+        ("TLS_RELOAD_INTERVAL", "1")
+    ]).await;
+    let (_, certificate) = https_get(server.address(), "/openapi.json").await;
+    assert_eq!(certificate, certificates("server.pem")[0]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    fs::copy(cert_path("server-renewed.pem"), &cert_file).expect("certificate renewed");
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    //  The new certificate is used by the connections accepted after the check
+    let mut renewed = false;
+    for _ in 0..3 {
+        let (status, certificate) = https_get(server.address(), "/openapi.json").await;
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        if certificate == certificates("server-renewed.pem")[0] {
+            renewed = true;
+            break;
+        }
+    }
+    assert!(renewed);
+}
+
+#[tokio::test]
+async fn middleware_is_called_with_mutual_tls() {
+    let mock = MockMiddleware::new();
+    let middleware_host = spawn_tls_middleware(mock.clone()).await;
+    let server = TestServer::start_with_middleware_host(mock, middleware_host, &[
+        ("MIDDLEWARE_TLS_CA_FILE", &cert_path("ca.pem")),
+        ("MIDDLEWARE_TLS_CERT_FILE", &cert_path("client.pem")),
+        ("MIDDLEWARE_TLS_KEY_FILE", &cert_path("client.key")),
+        ("MIDDLEWARE_TLS_DOMAIN", "localhost")
+    ], false).await;
+    let (status, _) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(create_request())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(server.mock.record_count("C_BPartner"), 1);
+}
+
+#[tokio::test]
+async fn middleware_rejects_a_channel_without_client_certificate() {
+    let mock = MockMiddleware::new();
+    let middleware_host = spawn_tls_middleware(mock.clone()).await;
+    let server = TestServer::start_with_middleware_host(mock, middleware_host, &[
+        ("MIDDLEWARE_TLS_CA_FILE", &cert_path("ca.pem")),
+        ("MIDDLEWARE_TLS_DOMAIN", "localhost")
+    ], false).await;
+    let (status, _) = server.request(Method::POST, "/v1/entities", Some(TOKEN), Some(create_request())).await;
+    //  With TLS 1.3 the certificate is rejected after the handshake, as a transport error
+    assert!(status.is_server_error());
+    assert_eq!(server.mock.record_count("C_BPartner"), 0);
+}
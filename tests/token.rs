@@ -0,0 +1,46 @@
+use std::collections::BTreeMap;
+use std::process::Command;
+
+use hmac::{Hmac, Mac};
+use jwt::VerifyWithKey;
+use serde_json::Value;
+use sha2::Sha256;
+
+fn token(arguments: &[&str], variables: &[(&str, &str)]) -> String {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_token"));
+    command.current_dir(std::env::temp_dir())
+        .env_remove("SERVER_PRIVATE_KEY")
+        .env_remove("SERVER_PRIVATE_KEY_FILE")
+        .args(["--subject", "11"])
+        .args(arguments);
+    for (key, value) in variables {
+        command.env(key, value);
+    }
+    let output = command.output().expect("token finished");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_owned()
+}
+
+fn is_signed_with(token: &str, secret: &str) -> bool {
+    let key: Hmac<Sha256> = Hmac::new_from_slice(secret.as_bytes()).expect("valid key");
+    let claims: Result<BTreeMap<String, Value>, _> = token.verify_with_key(&key);
+    claims.is_ok()
+}
+
+#[test]
+fn secret_file_argument_wins_over_the_environment() {
+    let file = std::env::temp_dir().join(format!("token-secret-{}", std::process::id()));
+    std::fs::write(&file, "file-secret\n").expect("secret file written");
+    let signed = token(&["--secret-file", file.to_str().unwrap()], &[("SERVER_PRIVATE_KEY", "environment-secret")]);
+    std::fs::remove_file(&file).ok();
+    assert!(is_signed_with(&signed, "file-secret"));
+    assert!(!is_signed_with(&signed, "environment-secret"));
+}
+
+#[test]
+fn secret_is_read_from_the_environment() {
+    let signed = token(&[], &[("SERVER_PRIVATE_KEY", "environment-secret")]);
+    assert!(is_signed_with(&signed, "environment-secret"));
+    let signed = token(&["--secret", "argument-secret"], &[("SERVER_PRIVATE_KEY", "environment-secret")]);
+    assert!(is_signed_with(&signed, "argument-secret"));
+}